@@ -1,6 +1,55 @@
+use crate::machine_system::beverages::async_beverage_server::BoxFuture;
+use crate::shared::{MaybeSendSync, Shared};
+
+pub mod async_drink_maker_beverage_server;
 pub mod drink_maker_beverage_server;
+pub mod drink_maker_command;
 pub mod drink_maker_display;
+pub mod stream_drink_maker;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum DrinkMakerError {
+    Nack(u32),
+    Timeout,
+    Unreachable,
+}
 
-pub trait DrinkMaker {
+pub trait DrinkMaker: MaybeSendSync {
     fn execute(&self, command: String);
+
+    fn try_execute(&self, command: String) -> Result<(), DrinkMakerError> {
+        self.execute(command);
+        Ok(())
+    }
+}
+
+pub trait AsyncDrinkMaker: MaybeSendSync {
+    fn execute(&self, command: String) -> BoxFuture<'_, ()>;
+
+    fn try_execute(&self, command: String) -> BoxFuture<'_, Result<(), DrinkMakerError>> {
+        Box::pin(async move {
+            self.execute(command).await;
+            Ok(())
+        })
+    }
+}
+
+pub struct SyncDrinkMakerAdapter {
+    drink_maker: Shared<dyn DrinkMaker>,
+}
+
+impl SyncDrinkMakerAdapter {
+    pub fn new(drink_maker: Shared<impl DrinkMaker + 'static>) -> Self {
+        Self { drink_maker }
+    }
+}
+
+impl AsyncDrinkMaker for SyncDrinkMakerAdapter {
+    fn execute(&self, command: String) -> BoxFuture<'_, ()> {
+        Box::pin(async move { self.drink_maker.execute(command) })
+    }
+
+    fn try_execute(&self, command: String) -> BoxFuture<'_, Result<(), DrinkMakerError>> {
+        Box::pin(async move { self.drink_maker.try_execute(command) })
+    }
 }
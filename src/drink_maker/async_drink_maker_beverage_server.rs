@@ -0,0 +1,58 @@
+use std::sync::Mutex;
+
+use super::drink_maker_beverage_server::{build_beverage_command, serving_failure};
+use crate::shared::Shared;
+use crate::{
+    drink_maker::AsyncDrinkMaker,
+    machine_system::beverages::{
+        add_on::AddOn,
+        async_beverage_server::{AsyncBeverageServer, BoxFuture},
+        beverage::Beverage,
+        beverage_catalog::BeverageCatalog,
+        beverage_server::ServingFailure,
+        cup_size::CupSize,
+        sugar_amount::SugarAmount,
+    },
+};
+
+pub struct AsyncDrinkMakerBeverageServer {
+    drink_maker: Shared<dyn AsyncDrinkMaker>,
+    beverage_catalog: Mutex<BeverageCatalog>,
+}
+
+impl AsyncDrinkMakerBeverageServer {
+    pub fn new(drink_maker: Shared<impl AsyncDrinkMaker + 'static>) -> Self {
+        Self {
+            drink_maker,
+            beverage_catalog: Mutex::new(BeverageCatalog::default()),
+        }
+    }
+}
+
+impl AsyncBeverageServer for AsyncDrinkMakerBeverageServer {
+    fn serve<'a>(
+        &'a self,
+        beverage: &'a Beverage,
+        sugar_amount: &'a SugarAmount,
+        cup_size: &'a CupSize,
+        add_ons: &'a [AddOn],
+    ) -> BoxFuture<'a, Result<(), ServingFailure>> {
+        let drink_maker_cmd = build_beverage_command(
+            beverage,
+            sugar_amount,
+            cup_size,
+            add_ons,
+            &self.beverage_catalog.lock().unwrap(),
+        );
+        Box::pin(async move {
+            self.drink_maker
+                .try_execute(drink_maker_cmd?)
+                .await
+                .map_err(serving_failure)
+        })
+    }
+
+    fn use_beverage_catalog(&self, beverage_catalog: &BeverageCatalog) {
+        *self.beverage_catalog.lock().unwrap() = beverage_catalog.clone();
+    }
+}
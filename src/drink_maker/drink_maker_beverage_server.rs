@@ -1,47 +1,78 @@
-use std::rc::Rc;
+use std::sync::Mutex;
 
+use crate::shared::Shared;
 use crate::{
-    drink_maker::DrinkMaker,
+    drink_maker::{drink_maker_command::DrinkMakerCommand, DrinkMaker, DrinkMakerError},
     machine_system::beverages::{
-        beverage::{Beverage, HotBeverageOption},
-        beverage_server::BeverageServer,
+        add_on::AddOn,
+        beverage::Beverage,
+        beverage_catalog::BeverageCatalog,
+        beverage_server::{BeverageServer, ServingFailure},
+        cup_size::CupSize,
         sugar_amount::SugarAmount,
     },
 };
 
 pub struct DrinkMakerBeverageServer {
-    drink_maker: Rc<dyn DrinkMaker>,
+    drink_maker: Shared<dyn DrinkMaker>,
+    beverage_catalog: Mutex<BeverageCatalog>,
 }
 
 impl DrinkMakerBeverageServer {
-    pub fn new(drink_maker: Rc<impl DrinkMaker + 'static>) -> Self {
-        Self { drink_maker }
+    pub fn new(drink_maker: Shared<impl DrinkMaker + 'static>) -> Self {
+        Self {
+            drink_maker,
+            beverage_catalog: Mutex::new(BeverageCatalog::default()),
+        }
     }
 }
 
 impl BeverageServer for DrinkMakerBeverageServer {
-    fn serve(&self, beverage: &Beverage, sugar_amount: &SugarAmount) {
-        let drink_maker_cmd = build_beverage_command(beverage, sugar_amount);
-        self.drink_maker.execute(drink_maker_cmd);
+    fn serve(
+        &self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) -> Result<(), ServingFailure> {
+        let drink_maker_cmd = build_beverage_command(
+            beverage,
+            sugar_amount,
+            cup_size,
+            add_ons,
+            &self.beverage_catalog.lock().unwrap(),
+        )?;
+        self.drink_maker
+            .try_execute(drink_maker_cmd)
+            .map_err(serving_failure)
     }
-}
 
-fn build_beverage_command(beverage: &Beverage, sugar_amount: &SugarAmount) -> String {
-    let beverage_cmd_part = match beverage {
-        Beverage::Coffee(HotBeverageOption::Standard) => "C",
-        Beverage::Coffee(HotBeverageOption::ExtraHot) => "Ch",
-        Beverage::Tea(HotBeverageOption::Standard) => "T",
-        Beverage::Tea(HotBeverageOption::ExtraHot) => "Th",
-        Beverage::HotChocolate(HotBeverageOption::Standard) => "H",
-        Beverage::HotChocolate(HotBeverageOption::ExtraHot) => "Hh",
-        Beverage::OrangeJuice => "O",
-    };
+    fn use_beverage_catalog(&self, beverage_catalog: &BeverageCatalog) {
+        *self.beverage_catalog.lock().unwrap() = beverage_catalog.clone();
+    }
+}
 
-    let (sugar_amount_cmd_part, stick_cmd_part) = match sugar_amount {
-        SugarAmount::Zero => ("", ""),
-        SugarAmount::One => ("1", "0"),
-        SugarAmount::Two => ("2", "0"),
-    };
+pub(crate) fn serving_failure(error: DrinkMakerError) -> ServingFailure {
+    match error {
+        DrinkMakerError::Nack(error_code) => ServingFailure::Rejected(error_code),
+        DrinkMakerError::Timeout => ServingFailure::Timeout,
+        DrinkMakerError::Unreachable => ServingFailure::Unreachable,
+    }
+}
 
-    format!("{beverage_cmd_part}:{sugar_amount_cmd_part}:{stick_cmd_part}")
+pub(crate) fn build_beverage_command(
+    beverage: &Beverage,
+    sugar_amount: &SugarAmount,
+    cup_size: &CupSize,
+    add_ons: &[AddOn],
+    beverage_catalog: &BeverageCatalog,
+) -> Result<String, ServingFailure> {
+    DrinkMakerCommand::MakeBeverage {
+        beverage: beverage.clone(),
+        sugar_amount: sugar_amount.clone(),
+        cup_size: *cup_size,
+        add_ons: add_ons.to_vec(),
+    }
+    .encode(beverage_catalog)
+    .map_err(|_| ServingFailure::UnknownBeverage)
 }
@@ -0,0 +1,376 @@
+use std::str::FromStr;
+
+use crate::machine_system::beverages::{
+    add_on::AddOn,
+    beverage::Beverage,
+    beverage_catalog::BeverageCatalog,
+    cup_size::CupSize,
+    sugar_amount::{SugarAmount, Sweetener},
+};
+
+const STICK: &str = "0";
+const SUGAR_FREE_SUFFIX: &str = "s";
+const SMALL_CUP: &str = "S";
+const LARGE_CUP: &str = "L";
+const ADD_ON_CODES: [(AddOn, char); 5] = [
+    (AddOn::Milk, 'M'),
+    (AddOn::OatMilk, 'O'),
+    (AddOn::ExtraShot, 'X'),
+    (AddOn::Decaf, 'D'),
+    (AddOn::WhippedCream, 'W'),
+];
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum DrinkMakerCommand {
+    MakeBeverage {
+        beverage: Beverage,
+        sugar_amount: SugarAmount,
+        cup_size: CupSize,
+        add_ons: Vec<AddOn>,
+    },
+    ShowMessage(String),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ParseDrinkMakerCommandError {
+    UnknownCommand(String),
+    UnknownBeverage(String),
+    InvalidSugarAmount(String),
+    InvalidStick(String),
+    InvalidCupSize(String),
+    InvalidAddOn(String),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum EncodeDrinkMakerCommandError {
+    UnknownBeverage(String),
+}
+
+impl DrinkMakerCommand {
+    pub fn encode(
+        &self,
+        catalog: &BeverageCatalog,
+    ) -> Result<String, EncodeDrinkMakerCommandError> {
+        match self {
+            DrinkMakerCommand::MakeBeverage {
+                beverage,
+                sugar_amount,
+                cup_size,
+                add_ons,
+            } => {
+                let sugar_amount_cmd_part =
+                    match (sugar_amount.dose_count(), sugar_amount.sweetener()) {
+                        (0, _) => String::new(),
+                        (doses, Sweetener::Sugar) => doses.to_string(),
+                        (doses, Sweetener::SugarFree) => format!("{doses}{SUGAR_FREE_SUFFIX}"),
+                    };
+                let stick_cmd_part = if sugar_amount.has_stick() { STICK } else { "" };
+                let beverage_cmd_part = catalog.drink_maker_code_of(beverage).ok_or_else(|| {
+                    EncodeDrinkMakerCommandError::UnknownBeverage(String::from(beverage.id()))
+                })?;
+                let cup_size_cmd_part = match cup_size {
+                    CupSize::Small => SMALL_CUP,
+                    CupSize::Medium => "",
+                    CupSize::Large => LARGE_CUP,
+                };
+                let add_ons_cmd_part: String = AddOn::distinct(add_ons)
+                    .filter_map(|add_on| {
+                        ADD_ON_CODES
+                            .iter()
+                            .find(|(known, _)| *known == add_on)
+                            .map(|(_, code)| *code)
+                    })
+                    .collect();
+                let mut command =
+                    format!("{beverage_cmd_part}:{sugar_amount_cmd_part}:{stick_cmd_part}");
+                if !cup_size_cmd_part.is_empty() || !add_ons_cmd_part.is_empty() {
+                    command.push_str(&format!(":{cup_size_cmd_part}"));
+                }
+                if !add_ons_cmd_part.is_empty() {
+                    command.push_str(&format!(":{add_ons_cmd_part}"));
+                }
+                Ok(command)
+            }
+            DrinkMakerCommand::ShowMessage(message) => Ok(format!("M:{message}")),
+        }
+    }
+
+    pub fn decode(
+        command: &str,
+        catalog: &BeverageCatalog,
+    ) -> Result<Self, ParseDrinkMakerCommandError> {
+        if let Some(message) = command.strip_prefix("M:") {
+            return Ok(DrinkMakerCommand::ShowMessage(String::from(message)));
+        }
+
+        let parts: Vec<&str> = command.split(':').collect();
+        let [beverage_cmd_part, sugar_amount_cmd_part, stick_cmd_part, ref optional_cmd_parts @ ..] =
+            parts[..]
+        else {
+            return Err(ParseDrinkMakerCommandError::UnknownCommand(String::from(
+                command,
+            )));
+        };
+        let (cup_size_cmd_part, add_ons_cmd_part) = match optional_cmd_parts {
+            [] => ("", ""),
+            [cup_size] => (*cup_size, ""),
+            [cup_size, add_ons] => (*cup_size, *add_ons),
+            _ => {
+                return Err(ParseDrinkMakerCommandError::UnknownCommand(String::from(
+                    command,
+                )))
+            }
+        };
+        let cup_size = match cup_size_cmd_part {
+            "" => CupSize::Medium,
+            SMALL_CUP => CupSize::Small,
+            LARGE_CUP => CupSize::Large,
+            other => {
+                return Err(ParseDrinkMakerCommandError::InvalidCupSize(String::from(
+                    other,
+                )))
+            }
+        };
+        let add_ons = add_ons_cmd_part
+            .chars()
+            .map(|code| {
+                ADD_ON_CODES
+                    .iter()
+                    .find(|(_, known)| *known == code)
+                    .map(|(add_on, _)| *add_on)
+                    .ok_or_else(|| ParseDrinkMakerCommandError::InvalidAddOn(code.to_string()))
+            })
+            .collect::<Result<Vec<AddOn>, _>>()?;
+
+        let beverage = catalog
+            .beverage_with_drink_maker_code(beverage_cmd_part)
+            .ok_or_else(|| {
+                ParseDrinkMakerCommandError::UnknownBeverage(String::from(beverage_cmd_part))
+            })?;
+        let invalid_sugar_amount =
+            || ParseDrinkMakerCommandError::InvalidSugarAmount(String::from(sugar_amount_cmd_part));
+        let (doses_cmd_part, sweetener) =
+            match sugar_amount_cmd_part.strip_suffix(SUGAR_FREE_SUFFIX) {
+                Some(doses) => (doses, Sweetener::SugarFree),
+                None => (sugar_amount_cmd_part, Sweetener::Sugar),
+            };
+        let sugar_amount = match doses_cmd_part {
+            "" if sweetener == Sweetener::Sugar => SugarAmount::Zero,
+            doses => match doses.parse::<u8>() {
+                Ok(doses @ 1..=SugarAmount::MAX_DOSES) if doses.to_string() == doses_cmd_part => {
+                    SugarAmount::doses(doses).with_sweetener(sweetener)
+                }
+                _ => return Err(invalid_sugar_amount()),
+            },
+        };
+        let sugar_amount = match stick_cmd_part {
+            STICK => sugar_amount.with_stick(),
+            "" if sugar_amount.dose_count() == 0 => sugar_amount,
+            other => {
+                return Err(ParseDrinkMakerCommandError::InvalidStick(String::from(
+                    other,
+                )))
+            }
+        };
+
+        Ok(DrinkMakerCommand::MakeBeverage {
+            beverage,
+            sugar_amount,
+            cup_size,
+            add_ons: AddOn::distinct(&add_ons).collect(),
+        })
+    }
+}
+
+impl std::fmt::Display for DrinkMakerCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let command = self
+            .encode(&BeverageCatalog::default())
+            .map_err(|_| std::fmt::Error)?;
+        write!(f, "{command}")
+    }
+}
+
+impl FromStr for DrinkMakerCommand {
+    type Err = ParseDrinkMakerCommandError;
+
+    fn from_str(command: &str) -> Result<Self, Self::Err> {
+        Self::decode(command, &BeverageCatalog::default())
+    }
+}
+
+impl std::fmt::Display for ParseDrinkMakerCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseDrinkMakerCommandError::UnknownCommand(command) => {
+                write!(f, "unknown drink maker command \"{command}\"")
+            }
+            ParseDrinkMakerCommandError::UnknownBeverage(code) => {
+                write!(f, "unknown beverage code \"{code}\"")
+            }
+            ParseDrinkMakerCommandError::InvalidSugarAmount(sugar_amount) => {
+                write!(
+                    f,
+                    "invalid sugar amount \"{sugar_amount}\", expected 1 to {}",
+                    SugarAmount::MAX_DOSES
+                )
+            }
+            ParseDrinkMakerCommandError::InvalidStick(stick) => write!(
+                f,
+                "invalid stick \"{stick}\", a stick is required whenever sugar is added"
+            ),
+            ParseDrinkMakerCommandError::InvalidCupSize(cup_size) => write!(
+                f,
+                "invalid cup size \"{cup_size}\", expected {SMALL_CUP} or {LARGE_CUP}"
+            ),
+            ParseDrinkMakerCommandError::InvalidAddOn(add_on) => {
+                write!(f, "unknown add-on code \"{add_on}\"")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseDrinkMakerCommandError {}
+
+impl std::fmt::Display for EncodeDrinkMakerCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeDrinkMakerCommandError::UnknownBeverage(id) => {
+                write!(f, "no drink maker code for beverage \"{id}\"")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncodeDrinkMakerCommandError {}
+
+#[cfg(test)]
+mod drink_maker_command_tests {
+    use test_case::test_case;
+
+    use super::{DrinkMakerCommand, EncodeDrinkMakerCommandError, ParseDrinkMakerCommandError};
+    use crate::machine_system::beverages::{
+        beverage::{Beverage, HotBeverageOption},
+        beverage_catalog::{BeverageCatalog, BeverageDefinition, Temperature},
+        cup_size::CupSize,
+        ingredient::Recipe,
+        sugar_amount::SugarAmount,
+    };
+
+    #[test_case("C::" ; "coffee")]
+    #[test_case("Ch:2:0" ; "extra hot coffee with two sugars")]
+    #[test_case("T:1:0" ; "tea with one sugar")]
+    #[test_case("Th::" ; "extra hot tea")]
+    #[test_case("H::" ; "hot chocolate")]
+    #[test_case("Hh:1:0" ; "extra hot hot chocolate with one sugar")]
+    #[test_case("O::" ; "orange juice")]
+    #[test_case("C:3:0" ; "coffee with three sugars")]
+    #[test_case("T:2s:0" ; "tea with two doses of sugar-free sweetener")]
+    #[test_case("C::0" ; "coffee with a stick and no sugar")]
+    #[test_case("C:1:0:S" ; "small coffee with one sugar")]
+    #[test_case("Th:::L" ; "large extra hot tea")]
+    #[test_case("C:1:0::MX" ; "coffee with milk and an extra shot")]
+    #[test_case("H:::L:W" ; "large hot chocolate with whipped cream")]
+    #[test_case("M:Sorry, tea is empty." ; "shortage message")]
+    #[test_case("M:0.6€" ; "missing money message")]
+    #[test_case("M:Change returned: 0.4€" ; "change message")]
+    fn commands_round_trip(command: &str) {
+        let parsed: DrinkMakerCommand = command.parse().unwrap();
+
+        assert_eq!(parsed.to_string(), command)
+    }
+
+    #[test]
+    fn parse_make_beverage_command() {
+        let parsed: DrinkMakerCommand = "Ch:2:0".parse().unwrap();
+
+        assert_eq!(
+            parsed,
+            DrinkMakerCommand::MakeBeverage {
+                beverage: Beverage::Coffee(HotBeverageOption::ExtraHot),
+                sugar_amount: SugarAmount::Two,
+                cup_size: CupSize::Medium,
+                add_ons: vec![],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_show_message_command_containing_separators() {
+        let parsed: DrinkMakerCommand = "M:Note: hot".parse().unwrap();
+
+        assert_eq!(
+            parsed,
+            DrinkMakerCommand::ShowMessage(String::from("Note: hot"))
+        )
+    }
+
+    #[test_case("", ParseDrinkMakerCommandError::UnknownCommand(String::from("")) ; "empty command")]
+    #[test_case("C:1", ParseDrinkMakerCommandError::UnknownCommand(String::from("C:1")) ; "missing stick part")]
+    #[test_case("X::", ParseDrinkMakerCommandError::UnknownBeverage(String::from("X")) ; "unknown beverage")]
+    #[test_case("Oh::", ParseDrinkMakerCommandError::UnknownBeverage(String::from("Oh")) ; "extra hot orange juice")]
+    #[test_case("C:0:0", ParseDrinkMakerCommandError::InvalidSugarAmount(String::from("0")) ; "zero sugars written out")]
+    #[test_case("C:s:0", ParseDrinkMakerCommandError::InvalidSugarAmount(String::from("s")) ; "sweetener without doses")]
+    #[test_case("C:x:0", ParseDrinkMakerCommandError::InvalidSugarAmount(String::from("x")) ; "unknown sugar amount")]
+    #[test_case("C:9:0", ParseDrinkMakerCommandError::InvalidSugarAmount(String::from("9")) ; "more sugar than the drink maker can add")]
+    #[test_case("C:6s:0", ParseDrinkMakerCommandError::InvalidSugarAmount(String::from("6s")) ; "more sweetener than the drink maker can add")]
+    #[test_case("C:1:", ParseDrinkMakerCommandError::InvalidStick(String::from("")) ; "sugar without stick")]
+    #[test_case("C::1", ParseDrinkMakerCommandError::InvalidStick(String::from("1")) ; "unknown stick")]
+    #[test_case("C:::M", ParseDrinkMakerCommandError::InvalidCupSize(String::from("M")) ; "unknown cup size")]
+    #[test_case("C::::L:", ParseDrinkMakerCommandError::UnknownCommand(String::from("C::::L:")) ; "too many parts")]
+    #[test_case("C::::Z", ParseDrinkMakerCommandError::InvalidAddOn(String::from("Z")) ; "unknown add-on")]
+    fn reject_malformed_commands(command: &str, expected_error: ParseDrinkMakerCommandError) {
+        assert_eq!(command.parse::<DrinkMakerCommand>(), Err(expected_error))
+    }
+
+    #[test_case("X::", "unknown beverage code \"X\"" ; "unknown beverage")]
+    #[test_case("C:9:0", "invalid sugar amount \"9\", expected 1 to 5" ; "out of range sugar amount")]
+    fn describe_parse_errors(command: &str, expected_description: &str) {
+        let error = command.parse::<DrinkMakerCommand>().unwrap_err();
+
+        assert_eq!(error.to_string(), expected_description)
+    }
+
+    #[test]
+    fn encode_and_decode_beverages_with_the_codes_of_a_catalog() {
+        let catalog = BeverageCatalog::default()
+            .with_definition(BeverageDefinition {
+                id: String::from("espresso"),
+                display_name: String::from("espresso"),
+                drink_maker_code: String::from("E"),
+                price: 80,
+                temperature: Temperature::Hot,
+                allowed_options: vec![HotBeverageOption::Standard, HotBeverageOption::ExtraHot],
+                allowed_add_ons: vec![],
+                max_sugar_doses: 2,
+                recipe: Recipe::default(),
+            })
+            .unwrap();
+        let command = DrinkMakerCommand::MakeBeverage {
+            beverage: Beverage::new("espresso", Some(HotBeverageOption::ExtraHot)),
+            sugar_amount: SugarAmount::One,
+            cup_size: CupSize::Medium,
+            add_ons: vec![],
+        };
+
+        assert_eq!(command.encode(&catalog), Ok(String::from("Eh:1:0")));
+        assert_eq!(DrinkMakerCommand::decode("Eh:1:0", &catalog), Ok(command))
+    }
+
+    #[test]
+    fn refuse_to_encode_a_beverage_missing_from_the_catalog() {
+        let command = DrinkMakerCommand::MakeBeverage {
+            beverage: Beverage::new("espresso", Some(HotBeverageOption::Standard)),
+            sugar_amount: SugarAmount::Zero,
+            cup_size: CupSize::Medium,
+            add_ons: vec![],
+        };
+
+        assert_eq!(
+            command.encode(&BeverageCatalog::default()),
+            Err(EncodeDrinkMakerCommandError::UnknownBeverage(String::from(
+                "espresso"
+            )))
+        )
+    }
+}
@@ -1,40 +1,111 @@
-use std::rc::Rc;
+use std::sync::Mutex;
 
+use crate::shared::Shared;
 use crate::{
-    drink_maker::DrinkMaker,
-    machine_system::{beverages::beverage::Beverage, display::Display},
+    drink_maker::{drink_maker_command::DrinkMakerCommand, DrinkMaker},
+    machine_system::{
+        beverages::{
+            beverage::Beverage, beverage_catalog::BeverageCatalog, ingredient::Ingredient,
+        },
+        display::Display,
+    },
 };
 
 pub struct DrinkMakerDisplay {
-    drink_maker: Rc<dyn DrinkMaker>,
+    drink_maker: Shared<dyn DrinkMaker>,
+    beverage_catalog: Mutex<BeverageCatalog>,
 }
 
 impl DrinkMakerDisplay {
-    pub fn new(drink_maker: Rc<impl DrinkMaker + 'static>) -> Self {
-        Self { drink_maker }
+    pub fn new(drink_maker: Shared<impl DrinkMaker + 'static>) -> Self {
+        Self {
+            drink_maker,
+            beverage_catalog: Mutex::new(BeverageCatalog::default()),
+        }
+    }
+
+    fn show_message(&self, message: String) {
+        self.drink_maker
+            .execute(DrinkMakerCommand::ShowMessage(message).to_string());
     }
 }
 
 impl Display for DrinkMakerDisplay {
     fn show_missing_money_message(&self, missing_money: u32) {
         let formatted_missing_money = missing_money as f32 / 100.0;
-        self.drink_maker
-            .execute(format!("M:{formatted_missing_money}€"));
+        self.show_message(format!("{formatted_missing_money}€"));
     }
 
     fn show_beverage_shortage_message(&self, beverage: &Beverage) {
-        self.drink_maker
-            .execute(format!("M:Sorry, {beverage} is empty."));
+        let beverage_name = self
+            .beverage_catalog
+            .lock()
+            .unwrap()
+            .display_name_of(beverage);
+        self.show_message(format!("Sorry, {beverage_name} is empty."));
+    }
+
+    fn show_missing_ingredient_message(&self, ingredient: &Ingredient) {
+        let ingredient_name = ingredient_name(ingredient);
+        self.show_message(format!("Sorry, we are out of {ingredient_name}."));
+    }
+
+    fn show_serving_failure_message(&self, beverage: &Beverage) {
+        let beverage_name = self
+            .beverage_catalog
+            .lock()
+            .unwrap()
+            .display_name_of(beverage);
+        self.show_message(format!(
+            "Sorry, unable to serve {beverage_name}. Money refunded."
+        ));
+    }
+
+    fn show_returned_change_message(&self, change: u32) {
+        let formatted_change = change as f32 / 100.0;
+        self.show_message(format!("Change returned: {formatted_change}€"));
+    }
+
+    fn show_exact_change_only_message(&self) {
+        self.show_message(String::from("Exact change only."));
+    }
+
+    fn show_unpayable_amount_message(&self, amount: u32) {
+        let formatted_amount = amount as f32 / 100.0;
+        self.show_message(format!("Cannot pay {formatted_amount}€ with coins."));
+    }
+
+    fn show_credit_message(&self, credit: u32) {
+        let formatted_credit = credit as f32 / 100.0;
+        self.show_message(format!("Credit: {formatted_credit}€"));
+    }
+
+    fn use_beverage_catalog(&self, beverage_catalog: &BeverageCatalog) {
+        *self.beverage_catalog.lock().unwrap() = beverage_catalog.clone();
+    }
+}
+
+fn ingredient_name(ingredient: &Ingredient) -> &'static str {
+    match ingredient {
+        Ingredient::CoffeeGrams => "coffee",
+        Ingredient::TeaBags => "tea",
+        Ingredient::ChocolatePowderGrams => "chocolate powder",
+        Ingredient::OrangeJuiceMl => "orange juice",
+        Ingredient::WaterMl => "water",
+        Ingredient::SugarDoses => "sugar",
+        Ingredient::SweetenerDoses => "sweetener",
+        Ingredient::StirringSticks => "stirring sticks",
+        Ingredient::MilkMl => "milk",
+        Ingredient::OatMilkMl => "oat milk",
+        Ingredient::DecafCoffeeGrams => "decaf coffee",
+        Ingredient::WhippedCreamGrams => "whipped cream",
+        Ingredient::ColdWaterMl => "cold water",
+        Ingredient::IceCubes => "ice",
     }
 }
 
 impl std::fmt::Display for Beverage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Beverage::Coffee(_) => write!(f, "coffee"),
-            Beverage::Tea(_) => write!(f, "tea"),
-            Beverage::HotChocolate(_) => write!(f, "hot chocolate"),
-            Beverage::OrangeJuice => write!(f, "orange juice"),
-        }
+        write!(f, "{}", self.id().replace('_', " "))
     }
 }
@@ -0,0 +1,582 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::drink_maker::{DrinkMaker, DrinkMakerError};
+use crate::shared::{MaybeSend, MaybeSendSync};
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl LineEnding {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+#[cfg(not(feature = "sync"))]
+type Connector<W> = Box<dyn Fn() -> io::Result<W>>;
+
+#[cfg(feature = "sync")]
+type Connector<W> = Box<dyn Fn() -> io::Result<W> + Send + Sync>;
+
+type AnswersReader = Box<dyn Read + Send>;
+
+#[cfg(not(feature = "sync"))]
+type ReaderOf<W> = Box<dyn Fn(&W) -> io::Result<AnswersReader>>;
+
+#[cfg(feature = "sync")]
+type ReaderOf<W> = Box<dyn Fn(&W) -> io::Result<AnswersReader> + Send + Sync>;
+
+const ACK: &str = "ACK";
+const NACK_PREFIX: &str = "NACK:";
+
+/// Identifies an acknowledged command by the connection it was sent over and
+/// its position among the commands sent over that connection.
+#[derive(Clone, Copy)]
+struct Ticket {
+    connection: u64,
+    sequence: u64,
+}
+
+struct Answers {
+    connection: u64,
+    lines: Receiver<String>,
+    sent: u64,
+    received: u64,
+}
+
+struct Acknowledgements<W> {
+    reader_of: ReaderOf<W>,
+    answers: Mutex<Option<Answers>>,
+    timeout: Duration,
+}
+
+impl<W> Acknowledgements<W> {
+    fn listen(&self, stream: &W) -> io::Result<()> {
+        let reader = (self.reader_of)(stream)?;
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut answers = self.answers.lock().unwrap();
+        let connection = answers.as_ref().map_or(0, |answers| answers.connection + 1);
+        *answers = Some(Answers {
+            connection,
+            lines,
+            sent: 0,
+            received: 0,
+        });
+        Ok(())
+    }
+
+    fn expect_answer(&self) -> Option<Ticket> {
+        let mut answers = self.answers.lock().unwrap();
+        let answers = answers.as_mut()?;
+        let ticket = Ticket {
+            connection: answers.connection,
+            sequence: answers.sent,
+        };
+        answers.sent += 1;
+        Some(ticket)
+    }
+
+    fn wait(&self, ticket: Ticket) -> Result<(), DrinkMakerError> {
+        let deadline = Instant::now() + self.timeout;
+        let mut answers = self.answers.lock().unwrap();
+        let answers = answers
+            .as_mut()
+            .filter(|answers| answers.connection == ticket.connection)
+            .ok_or(DrinkMakerError::Unreachable)?;
+
+        loop {
+            let line = answers
+                .lines
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .map_err(|error| match error {
+                    RecvTimeoutError::Timeout => DrinkMakerError::Timeout,
+                    RecvTimeoutError::Disconnected => DrinkMakerError::Unreachable,
+                })?;
+            let sequence = answers.received;
+            answers.received += 1;
+            // A late answer to a command that already timed out.
+            if sequence < ticket.sequence {
+                continue;
+            }
+            return answer_of(line.trim());
+        }
+    }
+}
+
+fn answer_of(line: &str) -> Result<(), DrinkMakerError> {
+    if line == ACK {
+        return Ok(());
+    }
+    match line
+        .strip_prefix(NACK_PREFIX)
+        .and_then(|error_code| error_code.parse().ok())
+    {
+        Some(error_code) => Err(DrinkMakerError::Nack(error_code)),
+        None => Err(DrinkMakerError::Unreachable),
+    }
+}
+
+pub struct StreamDrinkMaker<W: Write> {
+    stream: Mutex<Option<W>>,
+    connector: Connector<W>,
+    line_ending: LineEnding,
+    flush_after_each_command: bool,
+    reconnection_attempts: u32,
+    acknowledgements: Option<Acknowledgements<W>>,
+}
+
+impl<W: Write> StreamDrinkMaker<W> {
+    pub fn new(stream: W) -> Self {
+        Self {
+            stream: Mutex::new(Some(stream)),
+            connector: Box::new(|| {
+                Err(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "drink maker stream has no connector to reconnect with",
+                ))
+            }),
+            line_ending: LineEnding::default(),
+            flush_after_each_command: true,
+            reconnection_attempts: 0,
+            acknowledgements: None,
+        }
+    }
+
+    pub fn with_connector(connector: impl Fn() -> io::Result<W> + MaybeSendSync + 'static) -> Self {
+        Self {
+            stream: Mutex::new(None),
+            connector: Box::new(connector),
+            line_ending: LineEnding::default(),
+            flush_after_each_command: true,
+            reconnection_attempts: 0,
+            acknowledgements: None,
+        }
+    }
+
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    pub fn with_flush_after_each_command(mut self, flush_after_each_command: bool) -> Self {
+        self.flush_after_each_command = flush_after_each_command;
+        self
+    }
+
+    pub fn with_reconnection_attempts(mut self, reconnection_attempts: u32) -> Self {
+        self.reconnection_attempts = reconnection_attempts;
+        self
+    }
+
+    /// Reads the drink maker answers from `reader_of` each connected stream,
+    /// e.g. `TcpStream::try_clone`. The drink maker answers the commands sent
+    /// through `try_execute` in order; commands sent through `execute`, such
+    /// as display messages, are not answered.
+    pub fn with_acknowledgements<R: Read + Send + 'static>(
+        mut self,
+        reader_of: impl Fn(&W) -> io::Result<R> + MaybeSendSync + 'static,
+        timeout: Duration,
+    ) -> Self {
+        let acknowledgements = Acknowledgements {
+            reader_of: Box::new(move |stream: &W| {
+                reader_of(stream).map(|reader| Box::new(reader) as AnswersReader)
+            }),
+            answers: Mutex::new(None),
+            timeout,
+        };
+        let mut stream = self.stream.lock().unwrap();
+        if let Some(connected_stream) = stream.as_ref() {
+            if acknowledgements.listen(connected_stream).is_err() {
+                *stream = None;
+            }
+        }
+        drop(stream);
+        self.acknowledgements = Some(acknowledgements);
+        self
+    }
+
+    pub fn send(&self, command: &str) -> io::Result<()> {
+        self.send_line(command, false).map(|_| ())
+    }
+
+    fn send_line(&self, command: &str, expects_answer: bool) -> io::Result<Option<Ticket>> {
+        let line = format!("{command}{}", self.line_ending.as_str());
+
+        let mut result = self.write_line(&line, expects_answer);
+        for _ in 0..self.reconnection_attempts {
+            if result.is_ok() {
+                break;
+            }
+            result = self.write_line(&line, expects_answer);
+        }
+        result
+    }
+
+    fn write_line(&self, line: &str, expects_answer: bool) -> io::Result<Option<Ticket>> {
+        let mut stream = self.stream.lock().unwrap();
+        let connected_stream = match stream.as_mut() {
+            Some(connected_stream) => connected_stream,
+            None => {
+                let connected_stream = (self.connector)()?;
+                if let Some(acknowledgements) = &self.acknowledgements {
+                    acknowledgements.listen(&connected_stream)?;
+                }
+                stream.insert(connected_stream)
+            }
+        };
+
+        let written = connected_stream.write_all(line.as_bytes()).and_then(|_| {
+            if self.flush_after_each_command {
+                connected_stream.flush()
+            } else {
+                Ok(())
+            }
+        });
+        if written.is_err() {
+            *stream = None;
+        }
+        written?;
+        Ok(self
+            .acknowledgements
+            .as_ref()
+            .filter(|_| expects_answer)
+            .and_then(Acknowledgements::expect_answer))
+    }
+}
+
+impl StreamDrinkMaker<TcpStream> {
+    pub fn tcp(
+        address: impl ToSocketAddrs + MaybeSendSync + 'static,
+        write_timeout: Option<Duration>,
+    ) -> StreamDrinkMaker<TcpStream> {
+        Self::with_connector(move || {
+            let stream = TcpStream::connect(&address)?;
+            stream.set_write_timeout(write_timeout)?;
+            Ok(stream)
+        })
+    }
+}
+
+#[cfg(unix)]
+impl StreamDrinkMaker<std::os::unix::net::UnixStream> {
+    pub fn unix_socket(
+        path: impl Into<PathBuf>,
+        write_timeout: Option<Duration>,
+    ) -> StreamDrinkMaker<std::os::unix::net::UnixStream> {
+        let path = path.into();
+        Self::with_connector(move || {
+            let stream = std::os::unix::net::UnixStream::connect(&path)?;
+            stream.set_write_timeout(write_timeout)?;
+            Ok(stream)
+        })
+    }
+}
+
+impl StreamDrinkMaker<File> {
+    pub fn device(path: impl Into<PathBuf>) -> StreamDrinkMaker<File> {
+        let path = path.into();
+        Self::with_connector(move || OpenOptions::new().write(true).open(&path))
+    }
+}
+
+impl<W: Write + MaybeSend> DrinkMaker for StreamDrinkMaker<W> {
+    fn execute(&self, command: String) {
+        let _ = self.send(&command);
+    }
+
+    fn try_execute(&self, command: String) -> Result<(), DrinkMakerError> {
+        let ticket = self
+            .send_line(&command, true)
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => DrinkMakerError::Timeout,
+                _ => DrinkMakerError::Unreachable,
+            })?;
+
+        match (&self.acknowledgements, ticket) {
+            (Some(acknowledgements), Some(ticket)) => acknowledgements.wait(ticket),
+            (Some(_), None) => Err(DrinkMakerError::Unreachable),
+            (None, _) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod stream_drink_maker_tests {
+    use std::{
+        io::{self, BufRead, BufReader, Read, Write},
+        sync::Mutex,
+        thread,
+        time::{Duration, Instant},
+    };
+    use test_case::test_case;
+
+    #[cfg(unix)]
+    use std::os::unix::net::UnixStream;
+
+    use super::{LineEnding, StreamDrinkMaker};
+    use crate::drink_maker::{DrinkMaker, DrinkMakerError};
+    use crate::shared::Shared;
+
+    struct BrokenStream {}
+    impl Write for BrokenStream {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::from(io::ErrorKind::BrokenPipe))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    enum TestStream {
+        Broken(BrokenStream),
+        Working(Vec<u8>),
+    }
+
+    impl Write for TestStream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            match self {
+                TestStream::Broken(stream) => stream.write(buf),
+                TestStream::Working(stream) => stream.write(buf),
+            }
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(unix)]
+    fn answering_drink_maker(
+        hardware_end: std::os::unix::net::UnixStream,
+        answer: &'static str,
+    ) -> thread::JoinHandle<String> {
+        thread::spawn(move || {
+            let mut answers = hardware_end.try_clone().unwrap();
+            let mut received_command = String::new();
+            BufReader::new(hardware_end)
+                .read_line(&mut received_command)
+                .unwrap();
+            answers.write_all(answer.as_bytes()).unwrap();
+            received_command
+        })
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_newline_terminated_commands_over_a_socket() {
+        let (machine_end, hardware_end) = UnixStream::pair().unwrap();
+        let drink_maker = StreamDrinkMaker::new(machine_end);
+
+        drink_maker.execute(String::from("C:1:0"));
+        drink_maker.execute(String::from("M:Sorry, tea is empty."));
+
+        let mut received_lines = BufReader::new(hardware_end).lines();
+        assert_eq!(received_lines.next().unwrap().unwrap(), "C:1:0");
+        assert_eq!(
+            received_lines.next().unwrap().unwrap(),
+            "M:Sorry, tea is empty."
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_commands_with_the_configured_line_ending() {
+        let (machine_end, mut hardware_end) = UnixStream::pair().unwrap();
+        let drink_maker = StreamDrinkMaker::new(machine_end).with_line_ending(LineEnding::CrLf);
+
+        drink_maker.execute(String::from("T::"));
+        drop(drink_maker);
+
+        let mut received = String::new();
+        hardware_end.read_to_string(&mut received).unwrap();
+        assert_eq!(received, "T::\r\n");
+    }
+
+    #[test]
+    fn report_write_failures() {
+        let drink_maker = StreamDrinkMaker::new(BrokenStream {});
+
+        let sent = drink_maker.send("C::");
+
+        assert_eq!(sent.unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn reconnect_when_the_stream_breaks() {
+        let connections = Shared::new(Mutex::new(0));
+        let connections_counter = Shared::clone(&connections);
+        let drink_maker = StreamDrinkMaker::with_connector(move || {
+            *connections_counter.lock().unwrap() += 1;
+            if *connections_counter.lock().unwrap() == 1 {
+                Ok(TestStream::Broken(BrokenStream {}))
+            } else {
+                Ok(TestStream::Working(vec![]))
+            }
+        })
+        .with_reconnection_attempts(1);
+
+        let sent = drink_maker.send("H::");
+
+        assert!(sent.is_ok());
+        assert_eq!(*connections.lock().unwrap(), 2);
+        match drink_maker.stream.lock().unwrap().as_ref() {
+            Some(TestStream::Working(written)) => assert_eq!(written, b"H::\n"),
+            _ => panic!("expected a working stream"),
+        };
+    }
+
+    #[test]
+    fn give_up_after_the_configured_reconnection_attempts() {
+        let connections = Shared::new(Mutex::new(0));
+        let connections_counter = Shared::clone(&connections);
+        let drink_maker = StreamDrinkMaker::with_connector(move || {
+            *connections_counter.lock().unwrap() += 1;
+            Ok(BrokenStream {})
+        })
+        .with_reconnection_attempts(2);
+
+        let sent = drink_maker.send("O::");
+
+        assert!(sent.is_err());
+        assert_eq!(*connections.lock().unwrap(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test_case("ACK\n", Ok(()) ; "ack")]
+    #[test_case("NACK:3\n", Err(DrinkMakerError::Nack(3)) ; "nack with error code")]
+    #[test_case("JAMMED\n", Err(DrinkMakerError::Unreachable) ; "unreadable answer")]
+    fn report_the_drink_maker_answer(answer: &'static str, expected: Result<(), DrinkMakerError>) {
+        let (machine_end, hardware_end) = UnixStream::pair().unwrap();
+        let drink_maker = StreamDrinkMaker::new(machine_end)
+            .with_acknowledgements(UnixStream::try_clone, Duration::from_secs(1));
+        let drink_maker_hardware = answering_drink_maker(hardware_end, answer);
+
+        let executed = drink_maker.try_execute(String::from("C:1:0"));
+
+        assert_eq!(executed, expected);
+        assert_eq!(drink_maker_hardware.join().unwrap(), "C:1:0\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn report_a_timeout_when_the_drink_maker_does_not_answer() {
+        let (machine_end, _hardware_end) = UnixStream::pair().unwrap();
+        let drink_maker = StreamDrinkMaker::new(machine_end)
+            .with_acknowledgements(UnixStream::try_clone, Duration::from_millis(20));
+
+        let executed = drink_maker.try_execute(String::from("T::"));
+
+        assert_eq!(executed, Err(DrinkMakerError::Timeout));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn match_a_late_answer_to_the_command_that_timed_out() {
+        let (machine_end, hardware_end) = UnixStream::pair().unwrap();
+        let drink_maker = StreamDrinkMaker::new(machine_end)
+            .with_acknowledgements(UnixStream::try_clone, Duration::from_millis(200));
+        let drink_maker_hardware = thread::spawn(move || {
+            let mut answers = hardware_end.try_clone().unwrap();
+            let mut commands = BufReader::new(hardware_end).lines();
+            commands.next();
+            commands.next();
+            answers.write_all(b"ACK\nNACK:3\n").unwrap();
+        });
+
+        let first = drink_maker.try_execute(String::from("C::"));
+        let second = drink_maker.try_execute(String::from("T::"));
+
+        assert_eq!(first, Err(DrinkMakerError::Timeout));
+        assert_eq!(second, Err(DrinkMakerError::Nack(3)));
+        drink_maker_hardware.join().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn read_answers_from_the_reconnected_stream() {
+        let connections = Shared::new(Mutex::new(Vec::new()));
+        let drink_makers_hardware = Shared::clone(&connections);
+        let drink_maker = StreamDrinkMaker::with_connector(move || {
+            let (machine_end, hardware_end) = UnixStream::pair()?;
+            let mut drink_makers_hardware = drink_makers_hardware.lock().unwrap();
+            if drink_makers_hardware.is_empty() {
+                drop(hardware_end);
+                drink_makers_hardware.push(None);
+            } else {
+                drink_makers_hardware.push(Some(answering_drink_maker(hardware_end, "ACK\n")));
+            }
+            Ok(machine_end)
+        })
+        .with_reconnection_attempts(1)
+        .with_acknowledgements(UnixStream::try_clone, Duration::from_secs(1));
+
+        let executed = drink_maker.try_execute(String::from("H::"));
+
+        assert_eq!(executed, Ok(()));
+        let mut connections = connections.lock().unwrap();
+        assert_eq!(connections.len(), 2);
+        let drink_maker_hardware = connections.pop().unwrap().unwrap();
+        assert_eq!(drink_maker_hardware.join().unwrap(), "H::\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn do_not_wait_for_an_answer_to_a_display_message() {
+        let (machine_end, hardware_end) = UnixStream::pair().unwrap();
+        let drink_maker = StreamDrinkMaker::new(machine_end)
+            .with_acknowledgements(UnixStream::try_clone, Duration::from_secs(5));
+        let drink_maker_hardware = thread::spawn(move || {
+            let mut answers = hardware_end.try_clone().unwrap();
+            let mut commands = BufReader::new(hardware_end).lines();
+            commands.next();
+            commands.next();
+            answers.write_all(b"ACK\n").unwrap();
+        });
+        let started_at = Instant::now();
+
+        drink_maker.execute(String::from("M:Sorry, tea is empty."));
+        let executed = drink_maker.try_execute(String::from("C::"));
+
+        assert_eq!(executed, Ok(()));
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+        drink_maker_hardware.join().unwrap();
+    }
+
+    #[test]
+    fn report_unreachable_drink_maker_when_the_stream_is_broken() {
+        let drink_maker = StreamDrinkMaker::new(BrokenStream {});
+
+        let executed = drink_maker.try_execute(String::from("C::"));
+
+        assert_eq!(executed, Err(DrinkMakerError::Unreachable));
+    }
+}
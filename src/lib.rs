@@ -1,19 +1,60 @@
 pub mod drink_maker;
 pub mod machine_system;
+pub mod reports_printers;
+pub mod shared;
 
 pub mod prelude {
+    pub use crate::drink_maker::async_drink_maker_beverage_server::AsyncDrinkMakerBeverageServer;
     pub use crate::drink_maker::drink_maker_beverage_server::DrinkMakerBeverageServer;
+    pub use crate::drink_maker::drink_maker_command::{
+        DrinkMakerCommand, EncodeDrinkMakerCommandError, ParseDrinkMakerCommandError,
+    };
     pub use crate::drink_maker::drink_maker_display::DrinkMakerDisplay;
-    pub use crate::drink_maker::DrinkMaker;
+    pub use crate::drink_maker::stream_drink_maker::{LineEnding, StreamDrinkMaker};
+    pub use crate::drink_maker::{
+        AsyncDrinkMaker, DrinkMaker, DrinkMakerError, SyncDrinkMakerAdapter,
+    };
+    pub use crate::machine_system::beverages::add_on::AddOn;
+    pub use crate::machine_system::beverages::async_beverage_server::{
+        AsyncBeverageServer, BoxFuture, SyncBeverageServerAdapter,
+    };
     pub use crate::machine_system::beverages::beverage::*;
+    pub use crate::machine_system::beverages::beverage_catalog::{
+        BeverageCatalog, BeverageDefinition, InvalidBeverageDefinition, Temperature,
+    };
     pub use crate::machine_system::beverages::beverage_quantity_checker::BeverageQuantityChecker;
     pub use crate::machine_system::beverages::beverage_request::BeverageRequest;
-    pub use crate::machine_system::beverages::beverage_server::BeverageServer;
-    pub use crate::machine_system::beverages::sugar_amount::SugarAmount;
+    pub use crate::machine_system::beverages::beverage_server::{BeverageServer, ServingFailure};
+    pub use crate::machine_system::beverages::cup_size::CupSize;
+    pub use crate::machine_system::beverages::dispenser::{
+        DispensedBeverage, DispensedBeveragesHistory,
+    };
+    pub use crate::machine_system::beverages::ingredient::{Ingredient, Recipe};
+    pub use crate::machine_system::beverages::ingredient_inventory::IngredientInventory;
+    pub use crate::machine_system::beverages::sugar_amount::{SugarAmount, Sweetener};
+    pub use crate::machine_system::cashier::{Refund, UnsuccessfulPayment};
+    pub use crate::machine_system::clock::{Clock, ManualClock, SystemClock};
+    pub use crate::machine_system::coins::{Coin, CoinInventory};
+    pub use crate::machine_system::dispense_outcome::{
+        DispenseError, DispenseOutcome, InvalidBeverageRequest,
+    };
     pub use crate::machine_system::display::Display;
+    pub use crate::machine_system::event_store::{EventStore, FileEventStore, InMemoryEventStore};
+    pub use crate::machine_system::low_stock::LowStockThresholds;
     pub use crate::machine_system::machine::Machine;
     pub use crate::machine_system::machine_builder::*;
+    pub use crate::machine_system::machine_event::{MachineEvent, ParseMachineEventError};
+    pub use crate::machine_system::machine_event_listener::MachineEventListener;
+    pub use crate::machine_system::machine_state::{MachineState, ParseMachineStateError};
     pub use crate::machine_system::notifier::Notifier;
+    pub use crate::machine_system::price_list::PriceList;
     pub use crate::machine_system::reports_printer::PurchasesReport;
     pub use crate::machine_system::reports_printer::ReportsPrinter;
+    pub use crate::machine_system::reports_printer::RestockReport;
+    pub use crate::machine_system::restock::RestockRecord;
+    pub use crate::machine_system::sales_ledger::{LedgerEntry, SalesLedger, TransactionOutcome};
+    pub use crate::reports_printers::csv_reports_printer::CsvReportsPrinter;
+    pub use crate::reports_printers::json_reports_printer::JsonReportsPrinter;
+    pub use crate::reports_printers::text_reports_printer::TextReportsPrinter;
+    pub use crate::shared::Shared;
 }
@@ -1,7 +1,19 @@
 pub mod beverages;
-mod cashier;
+pub mod cashier;
+pub mod clock;
+pub mod coins;
+pub mod dispense_outcome;
 pub mod display;
+pub mod event_store;
+pub mod low_stock;
 pub mod machine;
 pub mod machine_builder;
+pub mod machine_event;
+pub mod machine_event_listener;
+pub mod machine_state;
 pub mod notifier;
+pub mod price_list;
+pub(crate) mod record_format;
 pub mod reports_printer;
+pub mod restock;
+pub mod sales_ledger;
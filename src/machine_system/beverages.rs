@@ -1,6 +1,12 @@
+pub mod add_on;
+pub mod async_beverage_server;
 pub mod beverage;
+pub mod beverage_catalog;
 pub mod beverage_quantity_checker;
 pub mod beverage_request;
 pub mod beverage_server;
+pub mod cup_size;
 pub(crate) mod dispenser;
+pub mod ingredient;
+pub mod ingredient_inventory;
 pub mod sugar_amount;
@@ -0,0 +1,50 @@
+#[derive(Eq, Hash, PartialEq, Ord, PartialOrd, Clone, Copy, Debug)]
+pub enum AddOn {
+    Milk,
+    OatMilk,
+    ExtraShot,
+    Decaf,
+    WhippedCream,
+}
+
+impl AddOn {
+    pub const ALL: [AddOn; 5] = [
+        AddOn::Milk,
+        AddOn::OatMilk,
+        AddOn::ExtraShot,
+        AddOn::Decaf,
+        AddOn::WhippedCream,
+    ];
+
+    pub(crate) fn distinct(add_ons: &[AddOn]) -> impl Iterator<Item = AddOn> + '_ {
+        AddOn::ALL
+            .into_iter()
+            .filter(|add_on| add_ons.contains(add_on))
+    }
+}
+
+impl std::fmt::Display for AddOn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddOn::Milk => write!(f, "milk"),
+            AddOn::OatMilk => write!(f, "oat milk"),
+            AddOn::ExtraShot => write!(f, "extra shot"),
+            AddOn::Decaf => write!(f, "decaf"),
+            AddOn::WhippedCream => write!(f, "whipped cream"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod add_on_tests {
+    use super::AddOn;
+
+    #[test]
+    fn distinct_add_ons_are_listed_once_in_a_stable_order() {
+        assert_eq!(
+            AddOn::distinct(&[AddOn::WhippedCream, AddOn::Milk, AddOn::WhippedCream])
+                .collect::<Vec<_>>(),
+            vec![AddOn::Milk, AddOn::WhippedCream]
+        )
+    }
+}
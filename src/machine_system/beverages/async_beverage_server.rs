@@ -0,0 +1,55 @@
+use std::{future::Future, pin::Pin};
+
+use super::{
+    add_on::AddOn,
+    beverage::Beverage,
+    beverage_catalog::BeverageCatalog,
+    beverage_server::{BeverageServer, ServingFailure},
+    cup_size::CupSize,
+    sugar_amount::SugarAmount,
+};
+use crate::shared::{MaybeSendSync, Shared};
+
+#[cfg(not(feature = "sync"))]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+#[cfg(feature = "sync")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+pub trait AsyncBeverageServer: MaybeSendSync {
+    fn serve<'a>(
+        &'a self,
+        beverage: &'a Beverage,
+        sugar_amount: &'a SugarAmount,
+        cup_size: &'a CupSize,
+        add_ons: &'a [AddOn],
+    ) -> BoxFuture<'a, Result<(), ServingFailure>>;
+
+    /// Receives the machine's catalog when the machine is built.
+    fn use_beverage_catalog(&self, _beverage_catalog: &BeverageCatalog) {}
+}
+
+pub struct SyncBeverageServerAdapter {
+    beverage_server: Shared<dyn BeverageServer>,
+}
+
+impl SyncBeverageServerAdapter {
+    pub fn new(beverage_server: Shared<dyn BeverageServer>) -> Self {
+        Self { beverage_server }
+    }
+}
+
+impl AsyncBeverageServer for SyncBeverageServerAdapter {
+    fn serve<'a>(
+        &'a self,
+        beverage: &'a Beverage,
+        sugar_amount: &'a SugarAmount,
+        cup_size: &'a CupSize,
+        add_ons: &'a [AddOn],
+    ) -> BoxFuture<'a, Result<(), ServingFailure>> {
+        Box::pin(async move {
+            self.beverage_server
+                .serve(beverage, sugar_amount, cup_size, add_ons)
+        })
+    }
+}
@@ -1,13 +1,167 @@
-#[derive(Eq, Hash, PartialEq, Clone, Debug)]
+#[derive(Eq, Hash, PartialEq, Ord, PartialOrd, Clone, Debug)]
 pub enum Beverage {
     Coffee(HotBeverageOption),
     Tea(HotBeverageOption),
     HotChocolate(HotBeverageOption),
     OrangeJuice,
+    Custom {
+        id: String,
+        option: Option<HotBeverageOption>,
+    },
+    /// A cold beverage served with ice or chilled; plain cold beverages keep
+    /// their usual value.
+    Cold {
+        id: String,
+        option: ColdBeverageOption,
+    },
 }
 
-#[derive(Eq, Hash, PartialEq, Clone, Debug)]
+#[derive(Eq, Hash, PartialEq, Ord, PartialOrd, Clone, Debug)]
 pub enum HotBeverageOption {
     Standard,
     ExtraHot,
 }
+
+#[derive(Eq, Hash, PartialEq, Ord, PartialOrd, Clone, Debug)]
+pub enum ColdBeverageOption {
+    NoIce,
+    WithIce,
+    Chilled,
+}
+
+impl Beverage {
+    pub fn new(id: &str, option: Option<HotBeverageOption>) -> Self {
+        match (id, option) {
+            ("coffee", Some(option)) => Beverage::Coffee(option),
+            ("tea", Some(option)) => Beverage::Tea(option),
+            ("hot_chocolate", Some(option)) => Beverage::HotChocolate(option),
+            ("orange_juice", None) => Beverage::OrangeJuice,
+            (id, option) => Beverage::Custom {
+                id: String::from(id),
+                option,
+            },
+        }
+    }
+
+    pub fn cold(id: &str, option: ColdBeverageOption) -> Self {
+        match option {
+            ColdBeverageOption::NoIce => Beverage::new(id, None),
+            option => Beverage::Cold {
+                id: String::from(id),
+                option,
+            },
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        match self {
+            Beverage::Coffee(_) => "coffee",
+            Beverage::Tea(_) => "tea",
+            Beverage::HotChocolate(_) => "hot_chocolate",
+            Beverage::OrangeJuice => "orange_juice",
+            Beverage::Custom { id, .. } | Beverage::Cold { id, .. } => id,
+        }
+    }
+
+    pub fn option(&self) -> Option<&HotBeverageOption> {
+        match self {
+            Beverage::Coffee(option) | Beverage::Tea(option) | Beverage::HotChocolate(option) => {
+                Some(option)
+            }
+            Beverage::OrangeJuice | Beverage::Cold { .. } => None,
+            Beverage::Custom { option, .. } => option.as_ref(),
+        }
+    }
+
+    pub fn cold_option(&self) -> Option<&ColdBeverageOption> {
+        match self {
+            Beverage::Cold { option, .. } => Some(option),
+            _ => None,
+        }
+    }
+
+    /// The beverage id prefixed or suffixed with its hot or cold option, such
+    /// as `extra_hot_coffee` or `orange_juice_with_ice`.
+    pub fn name(&self) -> String {
+        let id = self.id();
+        match (self.option(), self.cold_option()) {
+            (Some(HotBeverageOption::ExtraHot), _) => format!("extra_hot_{id}"),
+            (_, Some(ColdBeverageOption::WithIce)) => format!("{id}_with_ice"),
+            (_, Some(ColdBeverageOption::Chilled)) => format!("chilled_{id}"),
+            _ => String::from(id),
+        }
+    }
+
+    /// The same beverage written with its dedicated variant, so a `Custom`
+    /// or `Cold` value spelling a built-in beverage compares equal to it.
+    pub fn normalized(&self) -> Beverage {
+        match self {
+            Beverage::Custom { id, option } => Beverage::new(id, option.clone()),
+            Beverage::Cold { id, option } => Beverage::cold(id, option.clone()),
+            beverage => beverage.clone(),
+        }
+    }
+
+    pub fn without_cold_option(&self) -> Beverage {
+        match self {
+            Beverage::Cold { id, .. } => Beverage::new(id, None),
+            beverage => beverage.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod beverage_tests {
+    use test_case::test_case;
+
+    use super::{Beverage, ColdBeverageOption, HotBeverageOption};
+
+    #[test_case("coffee", Some(HotBeverageOption::ExtraHot), Beverage::Coffee(HotBeverageOption::ExtraHot) ; "coffee")]
+    #[test_case("orange_juice", None, Beverage::OrangeJuice ; "orange juice")]
+    #[test_case("lemonade", None, Beverage::Custom { id: String::from("lemonade"), option: None } ; "lemonade")]
+    fn beverages_built_from_their_id_match_the_known_beverages(
+        id: &str,
+        option: Option<HotBeverageOption>,
+        expected_beverage: Beverage,
+    ) {
+        let beverage = Beverage::new(id, option.clone());
+
+        assert_eq!(beverage, expected_beverage);
+        assert_eq!(beverage.id(), id);
+        assert_eq!(beverage.option(), option.as_ref())
+    }
+
+    #[test_case(Beverage::Coffee(HotBeverageOption::Standard), "coffee" ; "coffee")]
+    #[test_case(Beverage::HotChocolate(HotBeverageOption::ExtraHot), "extra_hot_hot_chocolate" ; "extra hot chocolate")]
+    #[test_case(Beverage::cold("orange_juice", ColdBeverageOption::WithIce), "orange_juice_with_ice" ; "orange juice with ice")]
+    #[test_case(Beverage::cold("lemonade", ColdBeverageOption::Chilled), "chilled_lemonade" ; "chilled lemonade")]
+    fn beverages_are_named_after_their_id_and_option(beverage: Beverage, expected_name: &str) {
+        assert_eq!(beverage.name(), expected_name)
+    }
+
+    #[test_case(Beverage::Custom { id: String::from("tea"), option: Some(HotBeverageOption::ExtraHot) }, Beverage::Tea(HotBeverageOption::ExtraHot) ; "custom tea")]
+    #[test_case(Beverage::Custom { id: String::from("orange_juice"), option: None }, Beverage::OrangeJuice ; "custom orange juice")]
+    #[test_case(Beverage::Cold { id: String::from("orange_juice"), option: ColdBeverageOption::NoIce }, Beverage::OrangeJuice ; "orange juice without ice")]
+    #[test_case(Beverage::Custom { id: String::from("lemonade"), option: None }, Beverage::Custom { id: String::from("lemonade"), option: None } ; "lemonade")]
+    fn custom_beverages_spelling_a_built_in_one_normalize_to_it(
+        beverage: Beverage,
+        expected_beverage: Beverage,
+    ) {
+        assert_eq!(beverage.normalized(), expected_beverage)
+    }
+
+    #[test_case("orange_juice", ColdBeverageOption::NoIce, Beverage::OrangeJuice ; "orange juice")]
+    #[test_case("orange_juice", ColdBeverageOption::WithIce, Beverage::Cold { id: String::from("orange_juice"), option: ColdBeverageOption::WithIce } ; "orange juice with ice")]
+    #[test_case("lemonade", ColdBeverageOption::Chilled, Beverage::Cold { id: String::from("lemonade"), option: ColdBeverageOption::Chilled } ; "chilled lemonade")]
+    fn cold_beverages_without_ice_keep_their_plain_value(
+        id: &str,
+        option: ColdBeverageOption,
+        expected_beverage: Beverage,
+    ) {
+        let beverage = Beverage::cold(id, option);
+
+        assert_eq!(beverage, expected_beverage);
+        assert_eq!(beverage.id(), id);
+        assert_eq!(beverage.without_cold_option(), Beverage::new(id, None))
+    }
+}
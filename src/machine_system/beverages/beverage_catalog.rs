@@ -0,0 +1,411 @@
+use super::{
+    add_on::AddOn,
+    beverage::{Beverage, ColdBeverageOption, HotBeverageOption},
+    ingredient::{Ingredient, Recipe},
+    sugar_amount::SugarAmount,
+};
+use crate::machine_system::price_list::PriceList;
+
+const EXTRA_HOT_CODE_SUFFIX: &str = "h";
+const WITH_ICE_CODE_SUFFIX: &str = "i";
+const CHILLED_CODE_SUFFIX: &str = "c";
+const DEFAULT_MAX_SUGAR_DOSES: u8 = 2;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Temperature {
+    Hot,
+    Cold,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BeverageDefinition {
+    pub id: String,
+    pub display_name: String,
+    pub drink_maker_code: String,
+    pub price: u32,
+    pub temperature: Temperature,
+    pub allowed_options: Vec<HotBeverageOption>,
+    pub allowed_add_ons: Vec<AddOn>,
+    pub max_sugar_doses: u8,
+    pub recipe: Recipe,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum InvalidBeverageDefinition {
+    InvalidId(String),
+}
+
+impl std::fmt::Display for InvalidBeverageDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidBeverageDefinition::InvalidId(id) => write!(
+                f,
+                "invalid beverage id \"{id}\", ids cannot be empty nor contain whitespace or '/'"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvalidBeverageDefinition {}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BeverageCatalog {
+    definitions: Vec<BeverageDefinition>,
+}
+
+impl BeverageCatalog {
+    pub fn empty() -> Self {
+        Self {
+            definitions: Vec::new(),
+        }
+    }
+
+    /// Ids end up in state files and event records, so they must be a single
+    /// word that the record formats can split on.
+    pub fn with_definition(
+        self,
+        definition: BeverageDefinition,
+    ) -> Result<Self, InvalidBeverageDefinition> {
+        let id = &definition.id;
+        if id.is_empty() || id.contains(|c: char| c.is_whitespace() || c == '/') {
+            return Err(InvalidBeverageDefinition::InvalidId(id.clone()));
+        }
+        Ok(self.with_known_definition(definition))
+    }
+
+    fn with_known_definition(mut self, definition: BeverageDefinition) -> Self {
+        self.definitions.retain(|known| known.id != definition.id);
+        self.definitions.push(definition);
+        self
+    }
+
+    pub fn definitions(&self) -> &[BeverageDefinition] {
+        &self.definitions
+    }
+
+    pub fn definition_of(&self, beverage: &Beverage) -> Option<&BeverageDefinition> {
+        self.definitions
+            .iter()
+            .find(|definition| definition.id == beverage.id())
+    }
+
+    pub fn contains(&self, beverage: &Beverage) -> bool {
+        self.definition_of(beverage).is_some_and(|definition| {
+            match (beverage.option(), beverage.cold_option()) {
+                (Some(option), _) => definition.allowed_options.contains(option),
+                (None, Some(_)) => definition.temperature == Temperature::Cold,
+                (None, None) => definition.allowed_options.is_empty(),
+            }
+        })
+    }
+
+    pub fn temperature_of(&self, beverage: &Beverage) -> Option<Temperature> {
+        self.definition_of(beverage)
+            .map(|definition| definition.temperature)
+    }
+
+    pub fn allows_add_on(&self, beverage: &Beverage, add_on: &AddOn) -> bool {
+        self.definition_of(beverage)
+            .is_some_and(|definition| definition.allowed_add_ons.contains(add_on))
+    }
+
+    pub fn max_sugar_doses_of(&self, beverage: &Beverage) -> u8 {
+        self.definition_of(beverage).map_or(0, |definition| {
+            definition.max_sugar_doses.min(SugarAmount::MAX_DOSES)
+        })
+    }
+
+    pub fn with_max_sugar_doses(mut self, beverage_id: &str, max_sugar_doses: u8) -> Self {
+        for definition in &mut self.definitions {
+            if definition.id == beverage_id {
+                definition.max_sugar_doses = max_sugar_doses;
+            }
+        }
+        self
+    }
+
+    pub fn beverages(&self) -> Vec<Beverage> {
+        let mut beverages = Vec::new();
+        for definition in &self.definitions {
+            if definition.allowed_options.is_empty() {
+                beverages.push(Beverage::new(&definition.id, None));
+            }
+            for option in &definition.allowed_options {
+                beverages.push(Beverage::new(&definition.id, Some(option.clone())));
+            }
+        }
+        beverages
+    }
+
+    pub fn display_name_of(&self, beverage: &Beverage) -> String {
+        match self.definition_of(beverage) {
+            Some(definition) => definition.display_name.clone(),
+            None => beverage.id().replace('_', " "),
+        }
+    }
+
+    pub fn drink_maker_code_of(&self, beverage: &Beverage) -> Option<String> {
+        let definition = self.definition_of(beverage)?;
+        let suffix = match (beverage.option(), beverage.cold_option()) {
+            (Some(HotBeverageOption::ExtraHot), _) => EXTRA_HOT_CODE_SUFFIX,
+            (_, Some(ColdBeverageOption::WithIce)) => WITH_ICE_CODE_SUFFIX,
+            (_, Some(ColdBeverageOption::Chilled)) => CHILLED_CODE_SUFFIX,
+            _ => "",
+        };
+        Some(format!("{}{suffix}", definition.drink_maker_code))
+    }
+
+    pub fn beverage_with_drink_maker_code(&self, code: &str) -> Option<Beverage> {
+        self.beverages()
+            .into_iter()
+            .flat_map(|beverage| self.with_cold_options(beverage))
+            .find(|beverage| self.drink_maker_code_of(beverage).as_deref() == Some(code))
+    }
+
+    fn with_cold_options(&self, beverage: Beverage) -> Vec<Beverage> {
+        if self.temperature_of(&beverage) != Some(Temperature::Cold) {
+            return vec![beverage];
+        }
+        let cold_beverages = [ColdBeverageOption::WithIce, ColdBeverageOption::Chilled]
+            .into_iter()
+            .map(|option| Beverage::cold(beverage.id(), option))
+            .collect::<Vec<_>>();
+        [vec![beverage], cold_beverages].concat()
+    }
+
+    pub fn price_list(&self) -> PriceList {
+        self.beverages()
+            .into_iter()
+            .fold(PriceList::empty(), |price_list, beverage| {
+                let price = self
+                    .definition_of(&beverage)
+                    .map_or(0, |definition| definition.price);
+                price_list.with_catalog_price(beverage, price)
+            })
+    }
+}
+
+impl Default for BeverageCatalog {
+    fn default() -> Self {
+        let hot_options = vec![HotBeverageOption::Standard, HotBeverageOption::ExtraHot];
+        BeverageCatalog::empty()
+            .with_known_definition(BeverageDefinition {
+                id: String::from("coffee"),
+                display_name: String::from("coffee"),
+                drink_maker_code: String::from("C"),
+                price: 60,
+                temperature: Temperature::Hot,
+                allowed_options: hot_options.clone(),
+                allowed_add_ons: AddOn::ALL.to_vec(),
+                max_sugar_doses: DEFAULT_MAX_SUGAR_DOSES,
+                recipe: Recipe::default()
+                    .with(Ingredient::CoffeeGrams, 7)
+                    .with(Ingredient::WaterMl, 150),
+            })
+            .with_known_definition(BeverageDefinition {
+                id: String::from("tea"),
+                display_name: String::from("tea"),
+                drink_maker_code: String::from("T"),
+                price: 40,
+                temperature: Temperature::Hot,
+                allowed_options: hot_options.clone(),
+                allowed_add_ons: vec![AddOn::Milk, AddOn::OatMilk],
+                max_sugar_doses: DEFAULT_MAX_SUGAR_DOSES,
+                recipe: Recipe::default()
+                    .with(Ingredient::TeaBags, 1)
+                    .with(Ingredient::WaterMl, 200),
+            })
+            .with_known_definition(BeverageDefinition {
+                id: String::from("hot_chocolate"),
+                display_name: String::from("hot chocolate"),
+                drink_maker_code: String::from("H"),
+                price: 50,
+                temperature: Temperature::Hot,
+                allowed_options: hot_options,
+                allowed_add_ons: vec![AddOn::Milk, AddOn::OatMilk, AddOn::WhippedCream],
+                max_sugar_doses: DEFAULT_MAX_SUGAR_DOSES,
+                recipe: Recipe::default()
+                    .with(Ingredient::ChocolatePowderGrams, 25)
+                    .with(Ingredient::WaterMl, 200),
+            })
+            .with_known_definition(BeverageDefinition {
+                id: String::from("orange_juice"),
+                display_name: String::from("orange juice"),
+                drink_maker_code: String::from("O"),
+                price: 60,
+                temperature: Temperature::Cold,
+                allowed_options: vec![],
+                allowed_add_ons: vec![],
+                max_sugar_doses: 0,
+                recipe: Recipe::default().with(Ingredient::OrangeJuiceMl, 200),
+            })
+    }
+}
+
+#[cfg(test)]
+mod beverage_catalog_tests {
+    use test_case::test_case;
+
+    use super::{BeverageCatalog, BeverageDefinition, InvalidBeverageDefinition, Temperature};
+    use crate::machine_system::beverages::{
+        add_on::AddOn,
+        beverage::{Beverage, ColdBeverageOption, HotBeverageOption},
+        ingredient::{Ingredient, Recipe},
+        sugar_amount::SugarAmount,
+    };
+
+    fn lemonade() -> BeverageDefinition {
+        BeverageDefinition {
+            id: String::from("lemonade"),
+            display_name: String::from("lemonade"),
+            drink_maker_code: String::from("L"),
+            price: 70,
+            temperature: Temperature::Cold,
+            allowed_options: vec![],
+            allowed_add_ons: vec![],
+            max_sugar_doses: 0,
+            recipe: Recipe::default().with(Ingredient::WaterMl, 250),
+        }
+    }
+
+    #[test]
+    fn default_catalog_lists_the_seven_historical_beverages() {
+        assert_eq!(
+            BeverageCatalog::default().beverages(),
+            vec![
+                Beverage::Coffee(HotBeverageOption::Standard),
+                Beverage::Coffee(HotBeverageOption::ExtraHot),
+                Beverage::Tea(HotBeverageOption::Standard),
+                Beverage::Tea(HotBeverageOption::ExtraHot),
+                Beverage::HotChocolate(HotBeverageOption::Standard),
+                Beverage::HotChocolate(HotBeverageOption::ExtraHot),
+                Beverage::OrangeJuice,
+            ]
+        )
+    }
+
+    #[test_case(Beverage::Coffee(HotBeverageOption::ExtraHot), "Ch" ; "extra hot coffee")]
+    #[test_case(Beverage::OrangeJuice, "O" ; "orange juice")]
+    #[test_case(Beverage::cold("orange_juice", ColdBeverageOption::WithIce), "Oi" ; "orange juice with ice")]
+    #[test_case(Beverage::cold("orange_juice", ColdBeverageOption::Chilled), "Oc" ; "chilled orange juice")]
+    #[test_case(Beverage::new("lemonade", None), "L" ; "lemonade")]
+    fn beverages_map_to_and_from_their_drink_maker_code(beverage: Beverage, code: &str) {
+        let catalog = BeverageCatalog::default()
+            .with_definition(lemonade())
+            .unwrap();
+
+        assert_eq!(
+            catalog.drink_maker_code_of(&beverage).as_deref(),
+            Some(code)
+        );
+        assert_eq!(catalog.beverage_with_drink_maker_code(code), Some(beverage))
+    }
+
+    #[test_case(Beverage::new("lemonade", None), true ; "defined beverage")]
+    #[test_case(Beverage::new("lemonade", Some(HotBeverageOption::ExtraHot)), false ; "option not allowed")]
+    #[test_case(Beverage::cold("lemonade", ColdBeverageOption::WithIce), true ; "cold beverage with ice")]
+    #[test_case(Beverage::cold("tea", ColdBeverageOption::Chilled), false ; "hot beverage chilled")]
+    #[test_case(Beverage::new("espresso", Some(HotBeverageOption::Standard)), false ; "undefined beverage")]
+    fn catalog_only_contains_defined_beverages_with_allowed_options(
+        beverage: Beverage,
+        expected: bool,
+    ) {
+        let catalog = BeverageCatalog::default()
+            .with_definition(lemonade())
+            .unwrap();
+
+        assert_eq!(catalog.contains(&beverage), expected)
+    }
+
+    #[test_case(Beverage::Coffee(HotBeverageOption::Standard), AddOn::ExtraShot, true ; "extra shot in a coffee")]
+    #[test_case(Beverage::Tea(HotBeverageOption::ExtraHot), AddOn::OatMilk, true ; "oat milk in a tea")]
+    #[test_case(Beverage::Tea(HotBeverageOption::Standard), AddOn::WhippedCream, false ; "whipped cream on a tea")]
+    #[test_case(Beverage::OrangeJuice, AddOn::Milk, false ; "milk in an orange juice")]
+    fn default_catalog_only_allows_add_ons_suited_to_each_beverage(
+        beverage: Beverage,
+        add_on: AddOn,
+        expected: bool,
+    ) {
+        assert_eq!(
+            BeverageCatalog::default().allows_add_on(&beverage, &add_on),
+            expected
+        )
+    }
+
+    #[test_case(Beverage::Tea(HotBeverageOption::ExtraHot), Some(Temperature::Hot) ; "tea")]
+    #[test_case(Beverage::cold("orange_juice", ColdBeverageOption::WithIce), Some(Temperature::Cold) ; "orange juice")]
+    #[test_case(Beverage::new("espresso", None), None ; "undefined beverage")]
+    fn default_catalog_classifies_beverages_as_hot_or_cold(
+        beverage: Beverage,
+        expected: Option<Temperature>,
+    ) {
+        assert_eq!(
+            BeverageCatalog::default().temperature_of(&beverage),
+            expected
+        )
+    }
+
+    #[test_case(Beverage::Tea(HotBeverageOption::Standard), 2 ; "tea")]
+    #[test_case(Beverage::OrangeJuice, 0 ; "orange juice")]
+    #[test_case(Beverage::new("espresso", None), 0 ; "undefined beverage")]
+    fn default_catalog_limits_the_sugar_of_each_beverage(beverage: Beverage, expected: u8) {
+        assert_eq!(
+            BeverageCatalog::default().max_sugar_doses_of(&beverage),
+            expected
+        )
+    }
+
+    #[test]
+    fn maximum_sugar_level_is_configurable_per_beverage() {
+        let catalog = BeverageCatalog::default().with_max_sugar_doses("coffee", 4);
+
+        assert_eq!(
+            catalog.max_sugar_doses_of(&Beverage::Coffee(HotBeverageOption::ExtraHot)),
+            4
+        )
+    }
+
+    #[test]
+    fn maximum_sugar_level_cannot_exceed_what_the_drink_maker_can_add() {
+        let catalog = BeverageCatalog::default().with_max_sugar_doses("coffee", 9);
+
+        assert_eq!(
+            catalog.max_sugar_doses_of(&Beverage::Coffee(HotBeverageOption::Standard)),
+            SugarAmount::MAX_DOSES
+        )
+    }
+
+    #[test]
+    fn price_list_is_derived_from_the_definitions() {
+        let catalog = BeverageCatalog::default()
+            .with_definition(lemonade())
+            .unwrap();
+
+        let price_list = catalog.price_list();
+
+        assert_eq!(
+            price_list.price_of(&Beverage::new("lemonade", None)),
+            Some(70)
+        );
+        assert_eq!(
+            price_list.price_of(&Beverage::Tea(HotBeverageOption::ExtraHot)),
+            Some(40)
+        );
+        assert_eq!(price_list.price_of(&Beverage::new("espresso", None)), None)
+    }
+
+    #[test_case("iced tea" ; "whitespace")]
+    #[test_case("tea/2" ; "slash")]
+    #[test_case("" ; "empty")]
+    fn catalog_rejects_ids_that_records_cannot_hold(id: &str) {
+        let definition = BeverageDefinition {
+            id: String::from(id),
+            ..lemonade()
+        };
+
+        assert_eq!(
+            BeverageCatalog::default().with_definition(definition),
+            Err(InvalidBeverageDefinition::InvalidId(String::from(id)))
+        )
+    }
+}
@@ -1,5 +1,51 @@
-use super::beverage::Beverage;
+use super::{
+    add_on::AddOn, beverage::Beverage, beverage_catalog::BeverageCatalog, cup_size::CupSize,
+    ingredient::Ingredient, sugar_amount::SugarAmount,
+};
+use crate::shared::MaybeSendSync;
 
-pub trait BeverageQuantityChecker {
-    fn is_empty(&self, beverage: &Beverage) -> bool;
+pub trait BeverageQuantityChecker: MaybeSendSync {
+    fn is_empty(
+        &self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) -> bool;
+
+    /// The ingredient that keeps the request from being served, when the
+    /// checker tracks ingredients.
+    fn missing_ingredient(
+        &self,
+        _beverage: &Beverage,
+        _sugar_amount: &SugarAmount,
+        _cup_size: &CupSize,
+        _add_ons: &[AddOn],
+    ) -> Option<Ingredient> {
+        None
+    }
+
+    fn consume(
+        &self,
+        _beverage: &Beverage,
+        _sugar_amount: &SugarAmount,
+        _cup_size: &CupSize,
+        _add_ons: &[AddOn],
+    ) {
+    }
+
+    fn remaining_servings(&self, _beverage: &Beverage) -> Option<u32> {
+        None
+    }
+
+    fn remaining_stock(&self, _ingredient: &Ingredient) -> Option<u32> {
+        None
+    }
+
+    fn refill(&self, _ingredient: Ingredient, _quantity: u32) {}
+
+    fn restore_stock(&self, _ingredient: Ingredient, _quantity: u32) {}
+
+    /// Receives the machine's catalog when the machine is built.
+    fn use_beverage_catalog(&self, _beverage_catalog: &BeverageCatalog) {}
 }
@@ -1,8 +1,10 @@
-use super::{beverage::Beverage, sugar_amount::SugarAmount};
+use super::{add_on::AddOn, beverage::Beverage, cup_size::CupSize, sugar_amount::SugarAmount};
 
 pub struct BeverageRequest<'a> {
     pub beverage: &'a Beverage,
     pub sugar_amount: &'a SugarAmount,
+    pub cup_size: CupSize,
+    pub add_ons: &'a [AddOn],
     pub money_amount: u32,
 }
 
@@ -11,7 +13,19 @@ impl<'a> BeverageRequest<'a> {
         Self {
             beverage,
             sugar_amount,
+            cup_size: CupSize::default(),
+            add_ons: &[],
             money_amount,
         }
     }
+
+    pub fn with_cup_size(mut self, cup_size: CupSize) -> Self {
+        self.cup_size = cup_size;
+        self
+    }
+
+    pub fn with_add_ons(mut self, add_ons: &'a [AddOn]) -> Self {
+        self.add_ons = add_ons;
+        self
+    }
 }
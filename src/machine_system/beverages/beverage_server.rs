@@ -1,5 +1,27 @@
-use super::{beverage::Beverage, sugar_amount::SugarAmount};
+use super::{
+    add_on::AddOn, beverage::Beverage, beverage_catalog::BeverageCatalog, cup_size::CupSize,
+    sugar_amount::SugarAmount,
+};
+use crate::shared::MaybeSendSync;
 
-pub trait BeverageServer {
-    fn serve(&self, beverage: &Beverage, sugar_amount: &SugarAmount);
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ServingFailure {
+    Rejected(u32),
+    Timeout,
+    Unreachable,
+    /// The beverage has no drink maker code in the machine's catalog.
+    UnknownBeverage,
+}
+
+pub trait BeverageServer: MaybeSendSync {
+    fn serve(
+        &self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) -> Result<(), ServingFailure>;
+
+    /// Receives the machine's catalog when the machine is built.
+    fn use_beverage_catalog(&self, _beverage_catalog: &BeverageCatalog) {}
 }
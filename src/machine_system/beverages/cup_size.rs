@@ -0,0 +1,35 @@
+#[derive(Eq, Hash, PartialEq, Ord, PartialOrd, Clone, Copy, Debug, Default)]
+pub enum CupSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+impl CupSize {
+    pub const ALL: [CupSize; 3] = [CupSize::Small, CupSize::Medium, CupSize::Large];
+
+    pub fn scale(&self, medium_quantity: u32) -> u32 {
+        match self {
+            CupSize::Small => (medium_quantity * 3).div_ceil(4),
+            CupSize::Medium => medium_quantity,
+            CupSize::Large => (medium_quantity * 3).div_ceil(2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod cup_size_tests {
+    use test_case::test_case;
+
+    use super::CupSize;
+
+    #[test_case(CupSize::Small, 150, 113 ; "small cups hold three quarters rounded up")]
+    #[test_case(CupSize::Medium, 150, 150 ; "medium cups hold the recipe quantity")]
+    #[test_case(CupSize::Large, 150, 225 ; "large cups hold half as much again")]
+    #[test_case(CupSize::Small, 1, 1 ; "small cups never drop a unit ingredient")]
+    #[test_case(CupSize::Large, 0, 0 ; "missing ingredients stay missing")]
+    fn scale_medium_quantities(cup_size: CupSize, medium_quantity: u32, expected_quantity: u32) {
+        assert_eq!(cup_size.scale(medium_quantity), expected_quantity)
+    }
+}
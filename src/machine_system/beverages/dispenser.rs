@@ -1,44 +1,86 @@
-use std::{collections::HashMap, rc::Rc};
+use std::{collections::HashMap, time::SystemTime};
 
 use super::{
-    beverage::Beverage, beverage_quantity_checker::BeverageQuantityChecker,
-    beverage_server::BeverageServer, sugar_amount::SugarAmount,
+    add_on::AddOn,
+    async_beverage_server::AsyncBeverageServer,
+    beverage::Beverage,
+    beverage_quantity_checker::BeverageQuantityChecker,
+    beverage_server::{BeverageServer, ServingFailure},
+    cup_size::CupSize,
+    ingredient::Ingredient,
+    sugar_amount::SugarAmount,
 };
+use crate::machine_system::sales_ledger::{LedgerEntry, TransactionOutcome};
+use crate::shared::Shared;
 
 pub(crate) enum BeverageDispsense {
     Ok,
-    Shortage,
+    Shortage {
+        missing_ingredient: Option<Ingredient>,
+    },
+    ServingFailed(ServingFailure),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct DispensedBeverage {
+    pub beverage: Beverage,
+    pub dispensed_at: SystemTime,
 }
 
 #[derive(Default)]
 pub struct DispensedBeveragesHistory {
     pub(crate) quantities: HashMap<Beverage, u32>,
+    pub(crate) dispensed: Vec<DispensedBeverage>,
 }
 
 impl DispensedBeveragesHistory {
-    pub fn record_dispensed_beverage<'a>(&'a mut self, beverage: &'a Beverage) {
+    pub fn from_ledger<'a>(entries: impl IntoIterator<Item = &'a LedgerEntry>) -> Self {
+        let mut history = Self::default();
+        for entry in entries
+            .into_iter()
+            .filter(|entry| entry.outcome == TransactionOutcome::Served)
+        {
+            history.record_dispensed_beverage(&entry.beverage, entry.occurred_at);
+        }
+        history
+    }
+
+    pub fn record_dispensed_beverage(&mut self, beverage: &Beverage, dispensed_at: SystemTime) {
         self.quantities
             .entry(beverage.clone())
             .and_modify(|counter| *counter += 1)
             .or_insert(1);
+        self.dispensed.push(DispensedBeverage {
+            beverage: beverage.clone(),
+            dispensed_at,
+        });
+    }
+
+    pub fn quantities(&self) -> &HashMap<Beverage, u32> {
+        &self.quantities
+    }
+
+    pub fn dispensed(&self) -> &[DispensedBeverage] {
+        &self.dispensed
     }
 }
 
 pub(crate) struct Dispenser {
-    beverage_server: Rc<dyn BeverageServer>,
-    beverage_quantity_checker: Rc<dyn BeverageQuantityChecker>,
-    dispensed_beverages_history: DispensedBeveragesHistory,
+    beverage_server: Shared<dyn BeverageServer>,
+    async_beverage_server: Shared<dyn AsyncBeverageServer>,
+    beverage_quantity_checker: Shared<dyn BeverageQuantityChecker>,
 }
 
 impl Dispenser {
     pub(crate) fn new(
-        beverage_server: Rc<dyn BeverageServer>,
-        beverage_quantity_checker: Rc<dyn BeverageQuantityChecker>,
+        beverage_server: Shared<dyn BeverageServer>,
+        async_beverage_server: Shared<dyn AsyncBeverageServer>,
+        beverage_quantity_checker: Shared<dyn BeverageQuantityChecker>,
     ) -> Self {
         Self {
             beverage_server,
+            async_beverage_server,
             beverage_quantity_checker,
-            dispensed_beverages_history: Default::default(),
         }
     }
 
@@ -46,18 +88,74 @@ impl Dispenser {
         &mut self,
         beverage: &Beverage,
         sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) -> BeverageDispsense {
+        if let Some(shortage) = self.shortage(beverage, sugar_amount, cup_size, add_ons) {
+            return shortage;
+        }
+        let served = self
+            .beverage_server
+            .serve(beverage, sugar_amount, cup_size, add_ons);
+        self.settle(beverage, sugar_amount, cup_size, add_ons, served)
+    }
+
+    pub(crate) async fn dispense_async(
+        &mut self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) -> BeverageDispsense {
+        if let Some(shortage) = self.shortage(beverage, sugar_amount, cup_size, add_ons) {
+            return shortage;
+        }
+        let served = self
+            .async_beverage_server
+            .serve(beverage, sugar_amount, cup_size, add_ons)
+            .await;
+        self.settle(beverage, sugar_amount, cup_size, add_ons, served)
+    }
+
+    fn shortage(
+        &self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) -> Option<BeverageDispsense> {
+        let checker = &self.beverage_quantity_checker;
+        checker
+            .is_empty(beverage, sugar_amount, cup_size, add_ons)
+            .then(|| BeverageDispsense::Shortage {
+                missing_ingredient: checker.missing_ingredient(
+                    beverage,
+                    sugar_amount,
+                    cup_size,
+                    add_ons,
+                ),
+            })
+    }
+
+    fn settle(
+        &self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+        served: Result<(), ServingFailure>,
     ) -> BeverageDispsense {
-        if self.beverage_quantity_checker.is_empty(beverage) {
-            BeverageDispsense::Shortage
-        } else {
-            self.beverage_server.serve(beverage, sugar_amount);
-            self.dispensed_beverages_history
-                .record_dispensed_beverage(beverage);
-            BeverageDispsense::Ok
+        match served {
+            Ok(()) => {
+                self.beverage_quantity_checker
+                    .consume(beverage, sugar_amount, cup_size, add_ons);
+                BeverageDispsense::Ok
+            }
+            Err(serving_failure) => BeverageDispsense::ServingFailed(serving_failure),
         }
     }
 
-    pub(crate) fn dispensed_beverages(&self) -> &DispensedBeveragesHistory {
-        &self.dispensed_beverages_history
+    pub(crate) fn beverage_quantity_checker(&self) -> &dyn BeverageQuantityChecker {
+        self.beverage_quantity_checker.as_ref()
     }
 }
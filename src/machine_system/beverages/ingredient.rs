@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use super::cup_size::CupSize;
+
+#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug)]
+pub enum Ingredient {
+    CoffeeGrams,
+    TeaBags,
+    ChocolatePowderGrams,
+    OrangeJuiceMl,
+    WaterMl,
+    SugarDoses,
+    SweetenerDoses,
+    StirringSticks,
+    MilkMl,
+    OatMilkMl,
+    DecafCoffeeGrams,
+    WhippedCreamGrams,
+    ColdWaterMl,
+    IceCubes,
+}
+
+impl Ingredient {
+    pub const ALL: [Ingredient; 14] = [
+        Ingredient::CoffeeGrams,
+        Ingredient::TeaBags,
+        Ingredient::ChocolatePowderGrams,
+        Ingredient::OrangeJuiceMl,
+        Ingredient::WaterMl,
+        Ingredient::SugarDoses,
+        Ingredient::SweetenerDoses,
+        Ingredient::StirringSticks,
+        Ingredient::MilkMl,
+        Ingredient::OatMilkMl,
+        Ingredient::DecafCoffeeGrams,
+        Ingredient::WhippedCreamGrams,
+        Ingredient::ColdWaterMl,
+        Ingredient::IceCubes,
+    ];
+}
+
+#[derive(Default, PartialEq, Eq, Debug, Clone)]
+pub struct Recipe {
+    pub(crate) quantities: HashMap<Ingredient, u32>,
+}
+
+impl Recipe {
+    pub fn with(mut self, ingredient: Ingredient, quantity: u32) -> Self {
+        self.quantities.insert(ingredient, quantity);
+        self
+    }
+
+    pub fn quantity_of(&self, ingredient: &Ingredient) -> u32 {
+        self.quantities.get(ingredient).copied().unwrap_or(0)
+    }
+
+    pub fn for_cup_size(&self, cup_size: &CupSize) -> Recipe {
+        Recipe {
+            quantities: self
+                .quantities
+                .iter()
+                .map(|(ingredient, quantity)| (*ingredient, cup_size.scale(*quantity)))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn combined_with(mut self, other: &Recipe) -> Recipe {
+        for (ingredient, quantity) in &other.quantities {
+            *self.quantities.entry(*ingredient).or_insert(0) += quantity;
+        }
+        self
+    }
+
+    pub(crate) fn decaffeinated(mut self) -> Recipe {
+        if let Some(coffee_grams) = self.quantities.remove(&Ingredient::CoffeeGrams) {
+            *self
+                .quantities
+                .entry(Ingredient::DecafCoffeeGrams)
+                .or_insert(0) += coffee_grams;
+        }
+        self
+    }
+}
@@ -0,0 +1,513 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use super::{
+    add_on::AddOn,
+    beverage::{Beverage, ColdBeverageOption},
+    beverage_catalog::BeverageCatalog,
+    beverage_quantity_checker::BeverageQuantityChecker,
+    cup_size::CupSize,
+    ingredient::{Ingredient, Recipe},
+    sugar_amount::SugarAmount,
+};
+
+pub struct IngredientInventory {
+    stock: Mutex<HashMap<Ingredient, u32>>,
+    catalog_recipes: Mutex<HashMap<Beverage, Recipe>>,
+    recipes: HashMap<Beverage, Recipe>,
+    add_on_recipes: HashMap<AddOn, Recipe>,
+    cold_option_recipes: HashMap<ColdBeverageOption, Recipe>,
+}
+
+impl IngredientInventory {
+    fn catalog_recipes_of(beverage_catalog: &BeverageCatalog) -> HashMap<Beverage, Recipe> {
+        beverage_catalog
+            .beverages()
+            .into_iter()
+            .filter_map(|beverage| {
+                let recipe = beverage_catalog.definition_of(&beverage)?.recipe.clone();
+                Some((beverage, recipe))
+            })
+            .collect()
+    }
+
+    pub fn with_stock(self, ingredient: Ingredient, quantity: u32) -> Self {
+        self.stock.lock().unwrap().insert(ingredient, quantity);
+        self
+    }
+
+    pub fn with_recipe(mut self, beverage: Beverage, recipe: Recipe) -> Self {
+        self.recipes.insert(beverage, recipe);
+        self
+    }
+
+    pub fn with_add_on_recipe(mut self, add_on: AddOn, recipe: Recipe) -> Self {
+        self.add_on_recipes.insert(add_on, recipe);
+        self
+    }
+
+    pub fn with_cold_option_recipe(mut self, option: ColdBeverageOption, recipe: Recipe) -> Self {
+        self.cold_option_recipes.insert(option, recipe);
+        self
+    }
+
+    pub fn stock_of(&self, ingredient: &Ingredient) -> u32 {
+        self.stock
+            .lock()
+            .unwrap()
+            .get(ingredient)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn recipe_of(&self, beverage: &Beverage, cup_size: &CupSize, add_ons: &[AddOn]) -> Recipe {
+        let catalog_recipes = self.catalog_recipes.lock().unwrap();
+        let plain_beverage = beverage.without_cold_option();
+        let mut recipe = [&self.recipes, &*catalog_recipes]
+            .into_iter()
+            .find_map(|recipes| {
+                recipes
+                    .get(beverage)
+                    .or_else(|| recipes.get(&plain_beverage))
+            })
+            .map(|recipe| recipe.for_cup_size(cup_size))
+            .unwrap_or_default();
+        if let Some(cold_option_recipe) = beverage
+            .cold_option()
+            .and_then(|option| self.cold_option_recipes.get(option))
+        {
+            recipe = recipe.combined_with(cold_option_recipe);
+        }
+        for add_on in AddOn::distinct(add_ons) {
+            if let Some(add_on_recipe) = self.add_on_recipes.get(&add_on) {
+                recipe = recipe.combined_with(add_on_recipe);
+            }
+            if add_on == AddOn::Decaf {
+                recipe = recipe.decaffeinated();
+            }
+        }
+        recipe
+    }
+
+    fn take(&self, ingredient: Ingredient, quantity: u32) {
+        let mut stock = self.stock.lock().unwrap();
+        let available = stock.entry(ingredient).or_insert(0);
+        *available = available.saturating_sub(quantity);
+    }
+}
+
+fn sweetening_recipe_of(sugar_amount: &SugarAmount) -> Recipe {
+    let recipe = Recipe::default()
+        .with(Ingredient::SugarDoses, sugar_amount.sugar_doses())
+        .with(Ingredient::SweetenerDoses, sugar_amount.sweetener_doses());
+    if sugar_amount.has_stick() {
+        recipe.with(Ingredient::StirringSticks, 1)
+    } else {
+        recipe
+    }
+}
+
+impl Default for IngredientInventory {
+    fn default() -> Self {
+        Self {
+            stock: Mutex::new(HashMap::new()),
+            catalog_recipes: Mutex::new(Self::catalog_recipes_of(&BeverageCatalog::default())),
+            recipes: HashMap::new(),
+            add_on_recipes: HashMap::from([
+                (AddOn::Milk, Recipe::default().with(Ingredient::MilkMl, 30)),
+                (
+                    AddOn::OatMilk,
+                    Recipe::default().with(Ingredient::OatMilkMl, 30),
+                ),
+                (
+                    AddOn::ExtraShot,
+                    Recipe::default().with(Ingredient::CoffeeGrams, 7),
+                ),
+                (
+                    AddOn::WhippedCream,
+                    Recipe::default().with(Ingredient::WhippedCreamGrams, 15),
+                ),
+            ]),
+            cold_option_recipes: HashMap::from([(
+                ColdBeverageOption::WithIce,
+                Recipe::default().with(Ingredient::IceCubes, 4),
+            )]),
+        }
+    }
+}
+
+impl BeverageQuantityChecker for IngredientInventory {
+    fn is_empty(
+        &self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) -> bool {
+        self.missing_ingredient(beverage, sugar_amount, cup_size, add_ons)
+            .is_some()
+    }
+
+    fn missing_ingredient(
+        &self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) -> Option<Ingredient> {
+        let recipe = self
+            .recipe_of(beverage, cup_size, add_ons)
+            .combined_with(&sweetening_recipe_of(sugar_amount));
+        Ingredient::ALL.into_iter().find(|ingredient| {
+            recipe
+                .quantities
+                .get(ingredient)
+                .is_some_and(|quantity| self.stock_of(ingredient) < *quantity)
+        })
+    }
+
+    fn consume(
+        &self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) {
+        let recipe = self
+            .recipe_of(beverage, cup_size, add_ons)
+            .combined_with(&sweetening_recipe_of(sugar_amount));
+        for (ingredient, quantity) in recipe.quantities {
+            self.take(ingredient, quantity);
+        }
+    }
+
+    fn remaining_servings(&self, beverage: &Beverage) -> Option<u32> {
+        self.recipe_of(beverage, &CupSize::default(), &[])
+            .quantities
+            .iter()
+            .filter(|(_, quantity)| **quantity > 0)
+            .map(|(ingredient, quantity)| self.stock_of(ingredient) / quantity)
+            .min()
+    }
+
+    fn remaining_stock(&self, ingredient: &Ingredient) -> Option<u32> {
+        Some(self.stock_of(ingredient))
+    }
+
+    fn refill(&self, ingredient: Ingredient, quantity: u32) {
+        *self.stock.lock().unwrap().entry(ingredient).or_insert(0) += quantity;
+    }
+
+    fn restore_stock(&self, ingredient: Ingredient, quantity: u32) {
+        self.stock.lock().unwrap().insert(ingredient, quantity);
+    }
+
+    fn use_beverage_catalog(&self, beverage_catalog: &BeverageCatalog) {
+        *self.catalog_recipes.lock().unwrap() = Self::catalog_recipes_of(beverage_catalog);
+    }
+}
+
+#[cfg(test)]
+mod ingredient_inventory_tests {
+    use test_case::test_case;
+
+    use super::IngredientInventory;
+    use crate::machine_system::{
+        beverages::{
+            add_on::AddOn,
+            beverage::{Beverage, ColdBeverageOption, HotBeverageOption},
+            beverage_quantity_checker::BeverageQuantityChecker,
+            beverage_request::BeverageRequest,
+            cup_size::CupSize,
+            ingredient::{Ingredient, Recipe},
+            sugar_amount::{SugarAmount, Sweetener},
+        },
+        machine::machine_tests::{
+            DummyBeverageServer, DummyDisplay, DummyNotifier, DummyReportsPrinter,
+        },
+        machine_builder::MachineBuilder,
+    };
+    use crate::shared::Shared;
+
+    fn full_inventory() -> IngredientInventory {
+        IngredientInventory::default()
+            .with_stock(Ingredient::CoffeeGrams, 1000)
+            .with_stock(Ingredient::TeaBags, 100)
+            .with_stock(Ingredient::ChocolatePowderGrams, 1000)
+            .with_stock(Ingredient::OrangeJuiceMl, 5000)
+            .with_stock(Ingredient::WaterMl, 10000)
+            .with_stock(Ingredient::SugarDoses, 100)
+            .with_stock(Ingredient::SweetenerDoses, 100)
+            .with_stock(Ingredient::StirringSticks, 100)
+            .with_stock(Ingredient::IceCubes, 100)
+    }
+
+    #[test_case(Beverage::Coffee(HotBeverageOption::Standard); "coffee")]
+    #[test_case(Beverage::Tea(HotBeverageOption::ExtraHot); "extra hot tea")]
+    #[test_case(Beverage::HotChocolate(HotBeverageOption::Standard); "hot chocolate")]
+    #[test_case(Beverage::OrangeJuice; "orange juice")]
+    fn beverages_are_available_when_every_ingredient_is_in_stock(beverage: Beverage) {
+        assert!(!full_inventory().is_empty(&beverage, &SugarAmount::Zero, &CupSize::Medium, &[]))
+    }
+
+    #[test_case(Ingredient::WaterMl, SugarAmount::One, Some(Ingredient::WaterMl) ; "water")]
+    #[test_case(Ingredient::SugarDoses, SugarAmount::One, Some(Ingredient::SugarDoses) ; "sugar")]
+    #[test_case(Ingredient::StirringSticks, SugarAmount::stick_only(), Some(Ingredient::StirringSticks) ; "stirring stick")]
+    #[test_case(Ingredient::SugarDoses, SugarAmount::Zero, None ; "sugar for an unsweetened tea")]
+    fn inventory_names_the_ingredient_missing_for_a_request(
+        out_of_stock: Ingredient,
+        sugar_amount: SugarAmount,
+        expected: Option<Ingredient>,
+    ) {
+        let inventory = full_inventory().with_stock(out_of_stock, 0);
+
+        assert_eq!(
+            inventory.missing_ingredient(
+                &Beverage::Tea(HotBeverageOption::Standard),
+                &sugar_amount,
+                &CupSize::Medium,
+                &[],
+            ),
+            expected
+        )
+    }
+
+    #[test]
+    fn beverage_is_empty_when_one_of_its_ingredients_is_missing() {
+        let inventory = full_inventory().with_stock(Ingredient::WaterMl, 100);
+
+        assert!(inventory.is_empty(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            &CupSize::Medium,
+            &[],
+        ));
+        assert!(!inventory.is_empty(
+            &Beverage::OrangeJuice,
+            &SugarAmount::Zero,
+            &CupSize::Medium,
+            &[]
+        ));
+    }
+
+    #[test]
+    fn custom_recipes_replace_the_default_ones() {
+        let inventory = full_inventory()
+            .with_stock(Ingredient::CoffeeGrams, 10)
+            .with_recipe(
+                Beverage::Coffee(HotBeverageOption::Standard),
+                Recipe::default().with(Ingredient::CoffeeGrams, 18),
+            );
+
+        assert!(inventory.is_empty(
+            &Beverage::Coffee(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            &CupSize::Medium,
+            &[],
+        ));
+    }
+
+    #[test_case(Ingredient::SugarDoses, 1; "one sugar dose left")]
+    #[test_case(Ingredient::StirringSticks, 0; "no stirring stick left")]
+    fn sweetened_beverage_is_empty_without_enough_sugar_or_sticks(
+        ingredient: Ingredient,
+        quantity: u32,
+    ) {
+        let inventory = full_inventory().with_stock(ingredient, quantity);
+        let coffee = Beverage::Coffee(HotBeverageOption::Standard);
+
+        assert!(!inventory.is_empty(&coffee, &SugarAmount::Zero, &CupSize::Medium, &[]));
+        assert!(inventory.is_empty(&coffee, &SugarAmount::Two, &CupSize::Medium, &[]));
+    }
+
+    #[test]
+    fn sweetened_beverage_is_empty_without_enough_sweetener() {
+        let inventory = full_inventory().with_stock(Ingredient::SweetenerDoses, 1);
+        let tea = Beverage::Tea(HotBeverageOption::Standard);
+        let two_sweeteners = SugarAmount::Two.with_sweetener(Sweetener::SugarFree);
+
+        assert!(!inventory.is_empty(&tea, &SugarAmount::Two, &CupSize::Medium, &[]));
+        assert!(inventory.is_empty(&tea, &two_sweeteners, &CupSize::Medium, &[]));
+    }
+
+    #[test_case(SugarAmount::Zero, 100, 100, 100; "no sugar, no stick")]
+    #[test_case(SugarAmount::One, 99, 100, 99; "one sugar and a stick")]
+    #[test_case(SugarAmount::Two, 98, 100, 99; "two sugars and a stick")]
+    #[test_case(SugarAmount::Two.with_sweetener(Sweetener::SugarFree), 100, 98, 99; "two sweeteners and a stick")]
+    #[test_case(SugarAmount::stick_only(), 100, 100, 99; "a stick without sugar")]
+    fn consume_ingredients_sugar_and_sticks(
+        sugar_amount: SugarAmount,
+        expected_sugar_doses: u32,
+        expected_sweetener_doses: u32,
+        expected_stirring_sticks: u32,
+    ) {
+        let inventory = full_inventory();
+
+        inventory.consume(
+            &Beverage::Coffee(HotBeverageOption::Standard),
+            &sugar_amount,
+            &CupSize::Medium,
+            &[],
+        );
+
+        assert_eq!(inventory.stock_of(&Ingredient::CoffeeGrams), 993);
+        assert_eq!(inventory.stock_of(&Ingredient::WaterMl), 9850);
+        assert_eq!(
+            inventory.stock_of(&Ingredient::SugarDoses),
+            expected_sugar_doses
+        );
+        assert_eq!(
+            inventory.stock_of(&Ingredient::SweetenerDoses),
+            expected_sweetener_doses
+        );
+        assert_eq!(
+            inventory.stock_of(&Ingredient::StirringSticks),
+            expected_stirring_sticks
+        );
+    }
+
+    #[test_case(CupSize::Small, 994, 9887 ; "small coffee")]
+    #[test_case(CupSize::Large, 989, 9775 ; "large coffee")]
+    fn consume_ingredients_scaled_to_the_cup_size(
+        cup_size: CupSize,
+        expected_coffee_grams: u32,
+        expected_water_ml: u32,
+    ) {
+        let inventory = full_inventory();
+
+        inventory.consume(
+            &Beverage::Coffee(HotBeverageOption::Standard),
+            &SugarAmount::One,
+            &cup_size,
+            &[],
+        );
+
+        assert_eq!(
+            inventory.stock_of(&Ingredient::CoffeeGrams),
+            expected_coffee_grams
+        );
+        assert_eq!(inventory.stock_of(&Ingredient::WaterMl), expected_water_ml);
+        assert_eq!(inventory.stock_of(&Ingredient::SugarDoses), 99);
+    }
+
+    #[test]
+    fn large_cups_need_more_stock_than_medium_ones() {
+        let inventory = full_inventory().with_stock(Ingredient::WaterMl, 200);
+        let coffee = Beverage::Coffee(HotBeverageOption::Standard);
+
+        assert!(!inventory.is_empty(&coffee, &SugarAmount::Zero, &CupSize::Medium, &[]));
+        assert!(inventory.is_empty(&coffee, &SugarAmount::Zero, &CupSize::Large, &[]));
+    }
+
+    #[test]
+    fn add_ons_consume_their_own_ingredients() {
+        let inventory = full_inventory()
+            .with_stock(Ingredient::MilkMl, 100)
+            .with_stock(Ingredient::DecafCoffeeGrams, 100);
+
+        inventory.consume(
+            &Beverage::Coffee(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            &CupSize::Medium,
+            &[AddOn::Milk, AddOn::ExtraShot, AddOn::Decaf],
+        );
+
+        assert_eq!(inventory.stock_of(&Ingredient::MilkMl), 70);
+        assert_eq!(inventory.stock_of(&Ingredient::DecafCoffeeGrams), 86);
+        assert_eq!(inventory.stock_of(&Ingredient::CoffeeGrams), 1000);
+    }
+
+    #[test]
+    fn beverage_is_empty_when_an_add_on_ingredient_is_missing() {
+        let inventory = full_inventory().with_stock(Ingredient::OatMilkMl, 10);
+        let tea = Beverage::Tea(HotBeverageOption::Standard);
+
+        assert!(!inventory.is_empty(&tea, &SugarAmount::Zero, &CupSize::Medium, &[]));
+        assert!(inventory.is_empty(
+            &tea,
+            &SugarAmount::Zero,
+            &CupSize::Medium,
+            &[AddOn::OatMilk]
+        ));
+    }
+
+    #[test_case(ColdBeverageOption::NoIce, 100 ; "no ice")]
+    #[test_case(ColdBeverageOption::WithIce, 96 ; "with ice")]
+    #[test_case(ColdBeverageOption::Chilled, 100 ; "chilled")]
+    fn cold_options_consume_their_own_ingredients(
+        option: ColdBeverageOption,
+        expected_ice_cubes: u32,
+    ) {
+        let inventory = full_inventory();
+
+        inventory.consume(
+            &Beverage::cold("orange_juice", option),
+            &SugarAmount::Zero,
+            &CupSize::Medium,
+            &[],
+        );
+
+        assert_eq!(inventory.stock_of(&Ingredient::OrangeJuiceMl), 4800);
+        assert_eq!(
+            inventory.stock_of(&Ingredient::IceCubes),
+            expected_ice_cubes
+        );
+    }
+
+    #[test]
+    fn beverage_is_empty_when_it_is_requested_with_ice_and_ice_is_missing() {
+        let inventory = full_inventory().with_stock(Ingredient::IceCubes, 3);
+
+        assert!(!inventory.is_empty(
+            &Beverage::cold("orange_juice", ColdBeverageOption::Chilled),
+            &SugarAmount::Zero,
+            &CupSize::Medium,
+            &[],
+        ));
+        assert!(inventory.is_empty(
+            &Beverage::cold("orange_juice", ColdBeverageOption::WithIce),
+            &SugarAmount::Zero,
+            &CupSize::Medium,
+            &[],
+        ));
+    }
+
+    #[test]
+    fn cold_beverages_can_be_made_with_cold_water() {
+        let lemonade = Beverage::cold("lemonade", ColdBeverageOption::WithIce);
+        let inventory = full_inventory()
+            .with_stock(Ingredient::ColdWaterMl, 300)
+            .with_recipe(
+                lemonade.clone(),
+                Recipe::default().with(Ingredient::ColdWaterMl, 250),
+            )
+            .with_cold_option_recipe(
+                ColdBeverageOption::WithIce,
+                Recipe::default().with(Ingredient::IceCubes, 6),
+            );
+
+        inventory.consume(&lemonade, &SugarAmount::Zero, &CupSize::Medium, &[]);
+
+        assert_eq!(inventory.stock_of(&Ingredient::ColdWaterMl), 50);
+        assert_eq!(inventory.stock_of(&Ingredient::IceCubes), 94);
+        assert!(inventory.is_empty(&lemonade, &SugarAmount::Zero, &CupSize::Medium, &[]));
+    }
+
+    #[test]
+    fn machine_consumes_ingredients_until_the_beverage_runs_out() {
+        let inventory = Shared::new(full_inventory().with_stock(Ingredient::TeaBags, 1));
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::clone(&inventory))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+        let tea = Beverage::Tea(HotBeverageOption::Standard);
+
+        machine.dispense(BeverageRequest::new(&tea, &SugarAmount::One, 40));
+
+        assert_eq!(inventory.stock_of(&Ingredient::TeaBags), 0);
+        assert_eq!(inventory.stock_of(&Ingredient::SugarDoses), 99);
+        assert!(inventory.is_empty(&tea, &SugarAmount::Zero, &CupSize::Medium, &[]));
+    }
+}
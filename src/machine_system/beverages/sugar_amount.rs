@@ -1,6 +1,88 @@
+#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug)]
+pub enum Sweetener {
+    Sugar,
+    SugarFree,
+}
+
 #[derive(Eq, Hash, PartialEq, Clone, Debug)]
 pub enum SugarAmount {
     Zero,
     One,
     Two,
+    Sweetened {
+        doses: u8,
+        sweetener: Sweetener,
+        stick: bool,
+    },
+}
+
+impl SugarAmount {
+    /// The most doses the drink maker can add to a single beverage.
+    pub const MAX_DOSES: u8 = 5;
+
+    pub fn doses(doses: u8) -> Self {
+        Self::from_parts(doses, Sweetener::Sugar, doses > 0)
+    }
+
+    pub fn stick_only() -> Self {
+        SugarAmount::Zero.with_stick()
+    }
+
+    pub fn with_sweetener(self, sweetener: Sweetener) -> Self {
+        Self::from_parts(self.dose_count(), sweetener, self.has_stick())
+    }
+
+    pub fn with_stick(self) -> Self {
+        Self::from_parts(self.dose_count(), self.sweetener(), true)
+    }
+
+    pub fn dose_count(&self) -> u8 {
+        match self {
+            SugarAmount::Zero => 0,
+            SugarAmount::One => 1,
+            SugarAmount::Two => 2,
+            SugarAmount::Sweetened { doses, .. } => *doses,
+        }
+    }
+
+    pub fn sweetener(&self) -> Sweetener {
+        match self {
+            SugarAmount::Sweetened { sweetener, .. } => *sweetener,
+            _ => Sweetener::Sugar,
+        }
+    }
+
+    pub fn has_stick(&self) -> bool {
+        match self {
+            SugarAmount::Sweetened { stick, .. } => *stick,
+            _ => self.dose_count() > 0,
+        }
+    }
+
+    pub fn sugar_doses(&self) -> u32 {
+        match self.sweetener() {
+            Sweetener::Sugar => self.dose_count() as u32,
+            Sweetener::SugarFree => 0,
+        }
+    }
+
+    pub fn sweetener_doses(&self) -> u32 {
+        match self.sweetener() {
+            Sweetener::Sugar => 0,
+            Sweetener::SugarFree => self.dose_count() as u32,
+        }
+    }
+
+    fn from_parts(doses: u8, sweetener: Sweetener, stick: bool) -> Self {
+        match (doses, sweetener, stick) {
+            (0, Sweetener::Sugar, false) => SugarAmount::Zero,
+            (1, Sweetener::Sugar, true) => SugarAmount::One,
+            (2, Sweetener::Sugar, true) => SugarAmount::Two,
+            _ => SugarAmount::Sweetened {
+                doses,
+                sweetener,
+                stick,
+            },
+        }
+    }
 }
@@ -1,67 +1,200 @@
-use super::beverages::beverage::Beverage;
+use std::time::SystemTime;
 
-pub(crate) enum UnsuccessfulPayment {
+use super::{
+    beverages::{add_on::AddOn, beverage::Beverage, cup_size::CupSize},
+    coins::{self, Coin, CoinInventory},
+    price_list::PriceList,
+};
+
+const MAX_CHANGE_TO_GUARANTEE: u32 = 195;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum UnsuccessfulPayment {
     NotEnoughMoney(u32),
+    ExactChangeOnly,
+    UnpayableAmount(u32),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Refund {
+    pub amount: u32,
+    pub refunded_at: SystemTime,
+}
+
+pub(crate) struct Payment {
+    inserted_coins: Vec<Coin>,
+    change: Vec<Coin>,
+}
+
+impl Payment {
+    pub(crate) fn new(inserted_coins: Vec<Coin>, change: Vec<Coin>) -> Self {
+        Self {
+            inserted_coins,
+            change,
+        }
+    }
+
+    pub(crate) fn inserted_coins(&self) -> &[Coin] {
+        &self.inserted_coins
+    }
+
+    pub(crate) fn change(&self) -> &[Coin] {
+        &self.change
+    }
+
+    pub(crate) fn change_amount(&self) -> u32 {
+        coins::total_value(&self.change)
+    }
+
+    pub(crate) fn paid_amount(&self) -> u32 {
+        coins::total_value(&self.inserted_coins)
+    }
 }
 
 pub(crate) struct Cashier {
     cash: Cash,
+    price_list: PriceList,
+    credit: Vec<Coin>,
+    refunds: Vec<Refund>,
 }
 
 impl Cashier {
-    pub(crate) fn new() -> Self {
-        Self { cash: Cash::new() }
+    pub(crate) fn new(price_list: PriceList, coin_inventory: CoinInventory) -> Self {
+        Self {
+            cash: Cash::new(coin_inventory),
+            price_list,
+            credit: vec![],
+            refunds: vec![],
+        }
+    }
+
+    pub(crate) fn update_price_list(&mut self, price_list: PriceList) {
+        self.price_list = price_list;
+    }
+
+    pub(crate) fn price_of(
+        &self,
+        beverage: &Beverage,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) -> Option<u32> {
+        self.price_list
+            .price_with_add_ons(beverage, cup_size, add_ons)
     }
 
     pub(crate) fn checkout_payment(
         &mut self,
-        beverage: &Beverage,
+        beverage_price: u32,
         money_amount: u32,
-    ) -> Result<(), UnsuccessfulPayment> {
-        let beverage_price = get_beverage_price(beverage);
-
-        if money_amount >= beverage_price {
-            self.cash.deposit(beverage_price);
-            Ok(())
-        } else {
-            Err(UnsuccessfulPayment::NotEnoughMoney(
+    ) -> Result<Payment, UnsuccessfulPayment> {
+        if money_amount < beverage_price {
+            return Err(UnsuccessfulPayment::NotEnoughMoney(
                 beverage_price - money_amount,
-            ))
+            ));
         }
+
+        let inserted_coins = Coin::split_amount(money_amount)
+            .ok_or(UnsuccessfulPayment::UnpayableAmount(money_amount))?;
+        self.collect_payment(beverage_price, inserted_coins)
     }
 
-    pub(crate) fn total_money_earned(&self) -> u32 {
-        self.cash.total_balance
+    pub(crate) fn insert_coin(&mut self, coin: Coin) {
+        self.credit.push(coin);
     }
 
-    pub(crate) fn refund_beverage_payment(&mut self, beverage: &Beverage) {
-        let beverage_price = get_beverage_price(beverage);
-        self.cash.withdrawn(beverage_price);
+    pub(crate) fn credit(&self) -> u32 {
+        coins::total_value(&self.credit)
     }
-}
 
-struct Cash {
-    total_balance: u32,
-}
+    pub(crate) fn checkout_credit(
+        &mut self,
+        beverage_price: u32,
+    ) -> Result<Payment, UnsuccessfulPayment> {
+        let credit = self.credit();
 
-impl Cash {
-    fn new() -> Self {
-        Self { total_balance: 0 }
+        if credit < beverage_price {
+            return Err(UnsuccessfulPayment::NotEnoughMoney(beverage_price - credit));
+        }
+
+        let payment = self.collect_payment(beverage_price, self.credit.clone())?;
+        self.credit.clear();
+        Ok(payment)
+    }
+
+    pub(crate) fn pending_credit(&self) -> &[Coin] {
+        &self.credit
     }
 
-    fn deposit(&mut self, amount: u32) {
-        self.total_balance += amount;
+    pub(crate) fn restore(&mut self, coin_inventory: CoinInventory, credit: Vec<Coin>) {
+        self.cash.coins = coin_inventory;
+        self.credit = credit;
     }
 
-    fn withdrawn(&mut self, amount: u32) {
-        self.total_balance -= amount;
+    pub(crate) fn cancel_credit(&mut self) -> Vec<Coin> {
+        std::mem::take(&mut self.credit)
     }
+
+    fn collect_payment(
+        &mut self,
+        beverage_price: u32,
+        inserted_coins: Vec<Coin>,
+    ) -> Result<Payment, UnsuccessfulPayment> {
+        let money_amount = coins::total_value(&inserted_coins);
+        self.cash.coins.add(&inserted_coins);
+        match self.cash.coins.make_change(money_amount - beverage_price) {
+            Some(change) => {
+                self.cash.coins.remove(&change);
+                Ok(Payment {
+                    inserted_coins,
+                    change,
+                })
+            }
+            None => {
+                self.cash.coins.remove(&inserted_coins);
+                Err(UnsuccessfulPayment::ExactChangeOnly)
+            }
+        }
+    }
+
+    pub(crate) fn replay_payment(&mut self, payment: &Payment, paid_with_credit: bool) {
+        self.cash.coins.add(&payment.inserted_coins);
+        self.cash.coins.remove(&payment.change);
+        if paid_with_credit {
+            self.credit.clear();
+        }
+    }
+
+    pub(crate) fn coin_inventory(&self) -> &CoinInventory {
+        &self.cash.coins
+    }
+
+    pub(crate) fn is_exact_change_only(&self) -> bool {
+        !self
+            .cash
+            .coins
+            .can_make_change_up_to(MAX_CHANGE_TO_GUARANTEE)
+    }
+
+    pub(crate) fn refund_beverage_payment(&mut self, payment: &Payment, refunded_at: SystemTime) {
+        self.cash.coins.add(&payment.change);
+        self.cash.coins.remove(&payment.inserted_coins);
+        self.refunds.push(Refund {
+            amount: payment.paid_amount(),
+            refunded_at,
+        });
+    }
+
+    pub(crate) fn refunds(&self) -> &[Refund] {
+        &self.refunds
+    }
+}
+
+struct Cash {
+    coins: CoinInventory,
 }
 
-fn get_beverage_price(beverage: &Beverage) -> u32 {
-    match beverage {
-        Beverage::Coffee(_) | Beverage::OrangeJuice => 60,
-        Beverage::Tea(_) => 40,
-        Beverage::HotChocolate(_) => 50,
+impl Cash {
+    fn new(coins: CoinInventory) -> Self {
+        Self { coins }
     }
 }
@@ -0,0 +1,80 @@
+use crate::shared::MaybeSendSync;
+use std::{
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+pub trait Clock: MaybeSendSync {
+    fn now(&self) -> SystemTime;
+}
+
+#[derive(Default)]
+pub struct SystemClock {}
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+pub struct ManualClock {
+    now: Mutex<SystemTime>,
+}
+
+impl ManualClock {
+    pub fn new(now: SystemTime) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new(SystemTime::UNIX_EPOCH)
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{Clock, ManualClock};
+
+    #[test]
+    fn manual_clock_only_moves_when_advanced() {
+        let clock = ManualClock::default();
+        assert_eq!(clock.now(), SystemTime::UNIX_EPOCH);
+
+        clock.advance(Duration::from_secs(90));
+
+        assert_eq!(
+            clock.now(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(90)
+        );
+    }
+
+    #[test]
+    fn manual_clock_can_be_set_to_any_time() {
+        let clock = ManualClock::default();
+        let noon = SystemTime::UNIX_EPOCH + Duration::from_secs(12 * 60 * 60);
+
+        clock.set(noon);
+
+        assert_eq!(clock.now(), noon);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+const STANDARD_FLOAT_COINS_PER_DENOMINATION: u32 = 10;
+
+#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug)]
+pub enum Coin {
+    FiveCents,
+    TenCents,
+    TwentyCents,
+    FiftyCents,
+    OneEuro,
+    TwoEuros,
+}
+
+impl Coin {
+    pub const ALL: [Coin; 6] = [
+        Coin::TwoEuros,
+        Coin::OneEuro,
+        Coin::FiftyCents,
+        Coin::TwentyCents,
+        Coin::TenCents,
+        Coin::FiveCents,
+    ];
+
+    pub fn value(&self) -> u32 {
+        match self {
+            Coin::FiveCents => 5,
+            Coin::TenCents => 10,
+            Coin::TwentyCents => 20,
+            Coin::FiftyCents => 50,
+            Coin::OneEuro => 100,
+            Coin::TwoEuros => 200,
+        }
+    }
+
+    pub(crate) fn split_amount(amount: u32) -> Option<Vec<Coin>> {
+        let mut remaining = amount;
+        let mut coins = vec![];
+        for coin in Coin::ALL {
+            while remaining >= coin.value() {
+                remaining -= coin.value();
+                coins.push(coin);
+            }
+        }
+
+        if remaining == 0 {
+            Some(coins)
+        } else {
+            None
+        }
+    }
+}
+
+pub(crate) fn total_value(coins: &[Coin]) -> u32 {
+    coins.iter().map(Coin::value).sum()
+}
+
+#[derive(Default, PartialEq, Eq, Debug, Clone)]
+pub struct CoinInventory {
+    coins: HashMap<Coin, u32>,
+}
+
+impl CoinInventory {
+    pub fn standard_float() -> Self {
+        Coin::ALL
+            .into_iter()
+            .filter(|coin| *coin != Coin::TwoEuros)
+            .fold(Self::default(), |inventory, coin| {
+                inventory.with_coins(coin, STANDARD_FLOAT_COINS_PER_DENOMINATION)
+            })
+    }
+
+    pub fn with_coins(mut self, coin: Coin, count: u32) -> Self {
+        *self.coins.entry(coin).or_insert(0) += count;
+        self
+    }
+
+    pub fn count(&self, coin: &Coin) -> u32 {
+        self.coins.get(coin).copied().unwrap_or(0)
+    }
+
+    pub fn total_value(&self) -> u32 {
+        self.coins
+            .iter()
+            .map(|(coin, count)| coin.value() * count)
+            .sum()
+    }
+
+    pub(crate) fn add(&mut self, coins: &[Coin]) {
+        for coin in coins {
+            *self.coins.entry(*coin).or_insert(0) += 1;
+        }
+    }
+
+    pub(crate) fn remove(&mut self, coins: &[Coin]) {
+        for coin in coins {
+            if let Some(count) = self.coins.get_mut(coin) {
+                *count -= 1;
+                if *count == 0 {
+                    self.coins.remove(coin);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn make_change(&self, amount: u32) -> Option<Vec<Coin>> {
+        let mut change = vec![];
+        if self.fill_change(amount, &Coin::ALL, &mut change) {
+            Some(change)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn can_make_change_up_to(&self, amount: u32) -> bool {
+        let smallest_coin_value = Coin::FiveCents.value();
+        (1..=amount / smallest_coin_value)
+            .all(|multiple| self.make_change(multiple * smallest_coin_value).is_some())
+    }
+
+    fn fill_change(&self, amount: u32, denominations: &[Coin], change: &mut Vec<Coin>) -> bool {
+        if amount == 0 {
+            return true;
+        }
+        let Some((coin, smaller_denominations)) = denominations.split_first() else {
+            return false;
+        };
+
+        let max_usable = (amount / coin.value()).min(self.count(coin));
+        for used in (0..=max_usable).rev() {
+            let change_len = change.len();
+            change.extend(std::iter::repeat_n(*coin, used as usize));
+            if self.fill_change(amount - used * coin.value(), smaller_denominations, change) {
+                return true;
+            }
+            change.truncate(change_len);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod coins_tests {
+    use test_case::test_case;
+
+    use super::{total_value, Coin, CoinInventory};
+
+    #[test_case(0, Some(vec![]); "nothing")]
+    #[test_case(85, Some(vec![Coin::FiftyCents, Coin::TwentyCents, Coin::TenCents, Coin::FiveCents]); "0.85€")]
+    #[test_case(300, Some(vec![Coin::TwoEuros, Coin::OneEuro]); "3€")]
+    #[test_case(59, None; "0.59€ is not payable with coins")]
+    fn split_an_amount_into_coins(amount: u32, expected_coins: Option<Vec<Coin>>) {
+        assert_eq!(Coin::split_amount(amount), expected_coins)
+    }
+
+    #[test]
+    fn make_change_with_the_largest_available_coins() {
+        let inventory = CoinInventory::default()
+            .with_coins(Coin::FiftyCents, 1)
+            .with_coins(Coin::TenCents, 5);
+
+        assert_eq!(
+            inventory.make_change(60),
+            Some(vec![Coin::FiftyCents, Coin::TenCents])
+        )
+    }
+
+    #[test]
+    fn make_change_when_the_largest_coin_leads_to_a_dead_end() {
+        let inventory = CoinInventory::default()
+            .with_coins(Coin::FiftyCents, 1)
+            .with_coins(Coin::TwentyCents, 3);
+
+        let change = inventory.make_change(60).unwrap();
+
+        assert_eq!(change, vec![Coin::TwentyCents; 3]);
+        assert_eq!(total_value(&change), 60)
+    }
+
+    #[test]
+    fn do_not_make_change_when_coins_are_missing() {
+        let inventory = CoinInventory::default().with_coins(Coin::TwentyCents, 1);
+
+        assert_eq!(inventory.make_change(40), None)
+    }
+
+    #[test]
+    fn standard_float_can_make_change_for_any_amount_up_to_two_euros() {
+        assert!(CoinInventory::standard_float().can_make_change_up_to(195));
+        assert!(!CoinInventory::default().can_make_change_up_to(195));
+    }
+}
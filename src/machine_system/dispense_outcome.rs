@@ -0,0 +1,65 @@
+use super::{
+    beverages::{add_on::AddOn, beverage_catalog::Temperature, beverage_server::ServingFailure},
+    cashier::UnsuccessfulPayment,
+    coins::Coin,
+};
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum DispenseOutcome {
+    Served { change: u32 },
+    Failed(DispenseError),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum DispenseError {
+    Payment(UnsuccessfulPayment),
+    Shortage {
+        refunded: u32,
+        refunded_coins: Vec<Coin>,
+    },
+    ServingFailed {
+        failure: ServingFailure,
+        refunded: u32,
+        refunded_coins: Vec<Coin>,
+    },
+    /// The drink maker could not be reached at all.
+    MachineUnavailable {
+        refunded: u32,
+        refunded_coins: Vec<Coin>,
+    },
+    InvalidRequest(InvalidBeverageRequest),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum InvalidBeverageRequest {
+    UnknownBeverage,
+    AddOnNotAllowed(AddOn),
+    SugarNotAllowed,
+    TooMuchSugar { max_doses: u8 },
+    TemperatureMismatch { served: Temperature },
+}
+
+impl std::fmt::Display for InvalidBeverageRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidBeverageRequest::UnknownBeverage => {
+                write!(f, "this beverage is not on the menu")
+            }
+            InvalidBeverageRequest::AddOnNotAllowed(add_on) => {
+                write!(f, "{add_on} cannot be added to this beverage")
+            }
+            InvalidBeverageRequest::SugarNotAllowed => {
+                write!(f, "this beverage cannot be sweetened")
+            }
+            InvalidBeverageRequest::TooMuchSugar { max_doses } => {
+                write!(f, "this beverage takes at most {max_doses} doses of sugar")
+            }
+            InvalidBeverageRequest::TemperatureMismatch { served } => match served {
+                Temperature::Hot => write!(f, "this beverage is only served hot"),
+                Temperature::Cold => write!(f, "this beverage is only served cold"),
+            },
+        }
+    }
+}
+
+impl std::error::Error for InvalidBeverageRequest {}
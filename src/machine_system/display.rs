@@ -1,6 +1,18 @@
-use super::beverages::beverage::Beverage;
+use super::beverages::{
+    beverage::Beverage, beverage_catalog::BeverageCatalog, ingredient::Ingredient,
+};
+use crate::shared::MaybeSendSync;
 
-pub trait Display {
+pub trait Display: MaybeSendSync {
     fn show_missing_money_message(&self, missing_money: u32);
     fn show_beverage_shortage_message(&self, beverage: &Beverage);
+    fn show_missing_ingredient_message(&self, ingredient: &Ingredient);
+    fn show_serving_failure_message(&self, beverage: &Beverage);
+    fn show_returned_change_message(&self, change: u32);
+    fn show_exact_change_only_message(&self);
+    fn show_unpayable_amount_message(&self, amount: u32);
+    fn show_credit_message(&self, credit: u32);
+
+    /// Receives the machine's catalog when the machine is built.
+    fn use_beverage_catalog(&self, _beverage_catalog: &BeverageCatalog) {}
 }
@@ -0,0 +1,159 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use super::machine_event::MachineEvent;
+use crate::shared::MaybeSendSync;
+
+pub trait EventStore: MaybeSendSync {
+    fn append(&self, event: &MachineEvent) -> io::Result<()>;
+    fn events(&self) -> io::Result<Vec<MachineEvent>>;
+}
+
+#[derive(Default)]
+pub struct InMemoryEventStore {
+    events: Mutex<Vec<MachineEvent>>,
+}
+
+impl EventStore for InMemoryEventStore {
+    fn append(&self, event: &MachineEvent) -> io::Result<()> {
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+
+    fn events(&self) -> io::Result<Vec<MachineEvent>> {
+        Ok(self.events.lock().unwrap().clone())
+    }
+}
+
+pub struct FileEventStore {
+    path: PathBuf,
+}
+
+impl FileEventStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl EventStore for FileEventStore {
+    fn append(&self, event: &MachineEvent) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(format!("{event}\n").as_bytes())?;
+        file.sync_data()
+    }
+
+    fn events(&self) -> io::Result<Vec<MachineEvent>> {
+        let log = match fs::read_to_string(&self.path) {
+            Ok(log) => log,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(error) => return Err(error),
+        };
+        // Every append ends with a newline, so a final line without one was
+        // torn by a crash mid-append. It is dropped from the log so the next
+        // append starts on a line of its own.
+        let complete_length = log.rfind('\n').map_or(0, |index| index + 1);
+        if complete_length < log.len() {
+            OpenOptions::new()
+                .write(true)
+                .open(&self.path)?
+                .set_len(complete_length as u64)?;
+        }
+        log[..complete_length]
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.parse()
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod event_store_tests {
+    use std::{
+        fs,
+        time::{Duration, SystemTime},
+    };
+
+    use super::{EventStore, FileEventStore};
+    use crate::machine_system::{
+        beverages::ingredient::Ingredient, coins::Coin, machine_event::MachineEvent,
+        restock::RestockRecord,
+    };
+
+    #[test]
+    fn file_event_store_appends_events_and_reads_them_back_in_order() {
+        let path = std::env::temp_dir().join(format!(
+            "event_store_tests_{}_append.log",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let store = FileEventStore::new(&path);
+        let events = vec![
+            MachineEvent::CoinInserted {
+                coin: Coin::OneEuro,
+                occurred_at: SystemTime::UNIX_EPOCH,
+            },
+            MachineEvent::CreditCancelled {
+                coins: vec![Coin::OneEuro],
+                occurred_at: SystemTime::UNIX_EPOCH + Duration::from_secs(5),
+            },
+            MachineEvent::Restocked(RestockRecord {
+                operator: String::from("alice\nbob"),
+                ingredient: Ingredient::TeaBags,
+                quantity: 10,
+                restocked_at: SystemTime::UNIX_EPOCH + Duration::from_secs(10),
+            }),
+        ];
+
+        for event in &events {
+            store.append(event).unwrap();
+        }
+
+        assert_eq!(FileEventStore::new(&path).events().unwrap(), events);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn file_event_store_drops_a_line_torn_by_a_crash_mid_append() {
+        let path =
+            std::env::temp_dir().join(format!("event_store_tests_{}_torn.log", std::process::id()));
+        let coin_inserted = MachineEvent::CoinInserted {
+            coin: Coin::OneEuro,
+            occurred_at: SystemTime::UNIX_EPOCH,
+        };
+        let credit_cancelled = MachineEvent::CreditCancelled {
+            coins: vec![Coin::OneEuro],
+            occurred_at: SystemTime::UNIX_EPOCH + Duration::from_secs(5),
+        };
+        let torn_line = &credit_cancelled.to_string()[..10];
+        fs::write(&path, format!("{coin_inserted}\n{torn_line}")).unwrap();
+        let store = FileEventStore::new(&path);
+
+        assert_eq!(store.events().unwrap(), vec![coin_inserted.clone()]);
+        store.append(&credit_cancelled).unwrap();
+        assert_eq!(
+            store.events().unwrap(),
+            vec![coin_inserted, credit_cancelled]
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn missing_event_log_has_no_events() {
+        let store = FileEventStore::new(std::env::temp_dir().join(format!(
+            "event_store_tests_{}_missing.log",
+            std::process::id()
+        )));
+
+        assert_eq!(store.events().unwrap(), vec![])
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+
+use super::{
+    beverages::{
+        beverage::Beverage, beverage_quantity_checker::BeverageQuantityChecker,
+        ingredient::Ingredient,
+    },
+    notifier::Notifier,
+};
+
+#[derive(Default, PartialEq, Eq, Debug, Clone)]
+pub struct LowStockThresholds {
+    beverages: Vec<(Beverage, u32)>,
+    ingredients: Vec<(Ingredient, u32)>,
+}
+
+impl LowStockThresholds {
+    pub fn for_beverage(mut self, beverage: Beverage, remaining_servings: u32) -> Self {
+        self.beverages.retain(|(known, _)| *known != beverage);
+        self.beverages.push((beverage, remaining_servings));
+        self
+    }
+
+    pub fn for_ingredient(mut self, ingredient: Ingredient, remaining_quantity: u32) -> Self {
+        self.ingredients.retain(|(known, _)| *known != ingredient);
+        self.ingredients.push((ingredient, remaining_quantity));
+        self
+    }
+}
+
+pub(crate) struct LowStockMonitor {
+    thresholds: LowStockThresholds,
+    low_beverages: HashSet<Beverage>,
+    low_ingredients: HashSet<Ingredient>,
+}
+
+impl LowStockMonitor {
+    pub(crate) fn new(thresholds: LowStockThresholds) -> Self {
+        Self {
+            thresholds,
+            low_beverages: HashSet::new(),
+            low_ingredients: HashSet::new(),
+        }
+    }
+
+    pub(crate) fn check(
+        &mut self,
+        beverage_quantity_checker: &dyn BeverageQuantityChecker,
+        notifier: &dyn Notifier,
+    ) {
+        for (beverage, threshold) in &self.thresholds.beverages {
+            let Some(remaining_servings) = beverage_quantity_checker.remaining_servings(beverage)
+            else {
+                continue;
+            };
+            if remaining_servings > *threshold {
+                self.low_beverages.remove(beverage);
+            } else if self.low_beverages.insert(beverage.clone()) {
+                notifier.notify_low_stock(beverage, remaining_servings);
+            }
+        }
+
+        for (ingredient, threshold) in &self.thresholds.ingredients {
+            let Some(remaining_quantity) = beverage_quantity_checker.remaining_stock(ingredient)
+            else {
+                continue;
+            };
+            if remaining_quantity > *threshold {
+                self.low_ingredients.remove(ingredient);
+            } else if self.low_ingredients.insert(*ingredient) {
+                notifier.notify_low_ingredient_stock(ingredient, remaining_quantity);
+            }
+        }
+    }
+}
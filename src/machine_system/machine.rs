@@ -1,147 +1,846 @@
-use std::rc::Rc;
+use std::{
+    collections::{HashMap, HashSet},
+    time::SystemTime,
+};
 
 use super::{
     beverages::{
+        add_on::AddOn,
         beverage::Beverage,
+        beverage_catalog::{BeverageCatalog, Temperature},
         beverage_request::BeverageRequest,
-        dispenser::{self, Dispenser},
+        beverage_server::ServingFailure,
+        cup_size::CupSize,
+        dispenser::{self, DispensedBeverage, DispensedBeveragesHistory, Dispenser},
+        ingredient::Ingredient,
         sugar_amount::SugarAmount,
     },
-    cashier::{self, Cashier},
+    cashier::{self, Cashier, Payment, Refund},
+    clock::Clock,
+    coins::{Coin, CoinInventory},
+    dispense_outcome::{DispenseError, DispenseOutcome, InvalidBeverageRequest},
     display::Display,
+    event_store::EventStore,
+    low_stock::LowStockMonitor,
+    machine_event::MachineEvent,
+    machine_event_listener::MachineEventListener,
+    machine_state::MachineState,
     notifier::Notifier,
-    reports_printer::{PurchasesReport, ReportsPrinter},
+    price_list::PriceList,
+    reports_printer::{PurchasesReport, ReportsPrinter, RestockReport},
+    restock::{RestockHistory, RestockRecord},
+    sales_ledger::{LedgerEntry, SalesLedger, TransactionOutcome},
 };
+use crate::shared::Shared;
 
 pub struct Machine {
     pub(crate) dispenser: Dispenser,
     pub(crate) cashier: Cashier,
-    pub(crate) display: Rc<dyn Display>,
-    pub(crate) reports_printer: Rc<dyn ReportsPrinter>,
-    pub(crate) notifier: Rc<dyn Notifier>,
+    pub(crate) display: Shared<dyn Display>,
+    pub(crate) reports_printer: Shared<dyn ReportsPrinter>,
+    pub(crate) notifier: Shared<dyn Notifier>,
+    pub(crate) low_stock_monitor: LowStockMonitor,
+    pub(crate) beverages_in_shortage: HashSet<(Beverage, CupSize)>,
+    pub(crate) restock_history: RestockHistory,
+    pub(crate) sales_ledger: SalesLedger,
+    pub(crate) period_first_entry: usize,
+    pub(crate) period_started_at: SystemTime,
+    pub(crate) closed_periods: Vec<PurchasesReport>,
+    pub(crate) clock: Shared<dyn Clock>,
+    pub(crate) event_store: Shared<dyn EventStore>,
+    pub(crate) listeners: Vec<Box<dyn MachineEventListener>>,
+    pub(crate) beverage_catalog: BeverageCatalog,
 }
 
 impl Machine {
-    pub fn dispense(&mut self, beverage_request: BeverageRequest) {
-        let payment = self
-            .cashier
-            .checkout_payment(beverage_request.beverage, beverage_request.money_amount);
+    pub fn dispense(&mut self, beverage_request: BeverageRequest) -> DispenseOutcome {
+        let beverage = beverage_request.beverage.normalized();
+        let request = BeverageRequest {
+            beverage: &beverage,
+            ..beverage_request
+        };
+        let payment = match self.charge(&request, false) {
+            Ok(payment) => payment,
+            Err(dispense_outcome) => return dispense_outcome,
+        };
+        let dispensed = self.dispenser.dispense(
+            request.beverage,
+            request.sugar_amount,
+            &request.cup_size,
+            request.add_ons,
+        );
+        self.complete(&request, payment, dispensed)
+    }
+
+    pub async fn dispense_async(
+        &mut self,
+        beverage_request: BeverageRequest<'_>,
+    ) -> DispenseOutcome {
+        let beverage = beverage_request.beverage.normalized();
+        let request = BeverageRequest {
+            beverage: &beverage,
+            ..beverage_request
+        };
+        let payment = match self.charge(&request, false) {
+            Ok(payment) => payment,
+            Err(dispense_outcome) => return dispense_outcome,
+        };
+        let dispensed = self
+            .dispenser
+            .dispense_async(
+                request.beverage,
+                request.sugar_amount,
+                &request.cup_size,
+                request.add_ons,
+            )
+            .await;
+        self.complete(&request, payment, dispensed)
+    }
+
+    pub fn insert_coin(&mut self, coin: Coin) {
+        self.cashier.insert_coin(coin);
+        self.record_event(MachineEvent::CoinInserted {
+            coin,
+            occurred_at: self.clock.now(),
+        });
+        self.display.show_credit_message(self.cashier.credit())
+    }
+
+    pub fn credit(&self) -> u32 {
+        self.cashier.credit()
+    }
+
+    pub fn select_beverage(
+        &mut self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+    ) -> DispenseOutcome {
+        self.select_sized_beverage(beverage, sugar_amount, &CupSize::default())
+    }
+
+    pub fn select_sized_beverage(
+        &mut self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+    ) -> DispenseOutcome {
+        self.select_customized_beverage(beverage, sugar_amount, cup_size, &[])
+    }
+
+    pub fn select_customized_beverage(
+        &mut self,
+        beverage: &Beverage,
+        sugar_amount: &SugarAmount,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) -> DispenseOutcome {
+        let beverage = beverage.normalized();
+        let request = BeverageRequest {
+            beverage: &beverage,
+            sugar_amount,
+            cup_size: *cup_size,
+            add_ons,
+            money_amount: self.cashier.credit(),
+        };
+        let payment = match self.charge(&request, true) {
+            Ok(payment) => payment,
+            Err(dispense_outcome) => return dispense_outcome,
+        };
+        let dispensed = self
+            .dispenser
+            .dispense(&beverage, sugar_amount, cup_size, add_ons);
+        self.complete(&request, payment, dispensed)
+    }
+
+    pub fn cancel(&mut self) -> Vec<Coin> {
+        let coins = self.cashier.cancel_credit();
+        self.record_event(MachineEvent::CreditCancelled {
+            coins: coins.clone(),
+            occurred_at: self.clock.now(),
+        });
+        coins
+    }
+
+    pub fn subscribe(&mut self, listener: Box<dyn MachineEventListener>) {
+        self.listeners.push(listener);
+    }
+
+    pub fn beverage_catalog(&self) -> &BeverageCatalog {
+        &self.beverage_catalog
+    }
+
+    fn validate_request(&self, request: &BeverageRequest) -> Result<u32, InvalidBeverageRequest> {
+        let BeverageRequest {
+            beverage,
+            sugar_amount,
+            ref cup_size,
+            add_ons,
+            ..
+        } = *request;
+        let served_temperature = self.beverage_catalog.temperature_of(beverage);
+        if beverage.cold_option().is_some() && served_temperature == Some(Temperature::Hot) {
+            return Err(InvalidBeverageRequest::TemperatureMismatch {
+                served: Temperature::Hot,
+            });
+        }
+        if beverage.option().is_some() && served_temperature == Some(Temperature::Cold) {
+            return Err(InvalidBeverageRequest::TemperatureMismatch {
+                served: Temperature::Cold,
+            });
+        }
+        if !self.beverage_catalog.contains(beverage) {
+            return Err(InvalidBeverageRequest::UnknownBeverage);
+        }
+        if let Some(add_on) = add_ons
+            .iter()
+            .find(|add_on| !self.beverage_catalog.allows_add_on(beverage, add_on))
+        {
+            return Err(InvalidBeverageRequest::AddOnNotAllowed(*add_on));
+        }
+        let max_sugar_doses = self.beverage_catalog.max_sugar_doses_of(beverage);
+        if sugar_amount.dose_count() > max_sugar_doses {
+            return Err(match max_sugar_doses {
+                0 => InvalidBeverageRequest::SugarNotAllowed,
+                _ => InvalidBeverageRequest::TooMuchSugar {
+                    max_doses: max_sugar_doses,
+                },
+            });
+        }
+        self.cashier
+            .price_of(beverage, cup_size, add_ons)
+            .ok_or(InvalidBeverageRequest::UnknownBeverage)
+    }
 
+    /// Validates the request and takes its payment, or records why it was
+    /// turned down.
+    fn charge(
+        &mut self,
+        request: &BeverageRequest,
+        paid_with_credit: bool,
+    ) -> Result<Payment, DispenseOutcome> {
+        let beverage_price = match self.validate_request(request) {
+            Ok(beverage_price) => beverage_price,
+            Err(invalid_request) => {
+                let dispense_outcome =
+                    DispenseOutcome::Failed(DispenseError::InvalidRequest(invalid_request));
+                self.record_transaction(request, &dispense_outcome);
+                return Err(dispense_outcome);
+            }
+        };
+        let payment = if paid_with_credit {
+            self.cashier.checkout_credit(beverage_price)
+        } else {
+            self.cashier
+                .checkout_payment(beverage_price, request.money_amount)
+        };
         match payment {
-            Ok(_) => self.handle_dispense(beverage_request.beverage, beverage_request.sugar_amount),
-            Err(cashier::UnsuccessfulPayment::NotEnoughMoney(missing_money_amount)) => self
+            Ok(payment) => {
+                self.record_event(MachineEvent::PaymentAccepted {
+                    beverage: request.beverage.clone(),
+                    inserted_coins: payment.inserted_coins().to_vec(),
+                    change: payment.change().to_vec(),
+                    paid_with_credit,
+                    occurred_at: self.clock.now(),
+                });
+                Ok(payment)
+            }
+            Err(unsuccessful_payment) => {
+                let dispense_outcome = self.reject_payment(unsuccessful_payment);
+                self.record_transaction(request, &dispense_outcome);
+                Err(dispense_outcome)
+            }
+        }
+    }
+
+    /// Settles a paid request once the dispenser is done with it.
+    fn complete(
+        &mut self,
+        request: &BeverageRequest,
+        payment: Payment,
+        dispensed: dispenser::BeverageDispsense,
+    ) -> DispenseOutcome {
+        let dispense_outcome = self.handle_dispensed(request, payment, dispensed);
+        self.record_transaction(request, &dispense_outcome);
+        dispense_outcome
+    }
+
+    fn record_event(&self, event: MachineEvent) {
+        if let Err(error) = self.event_store.append(&event) {
+            self.notifier.notify_event_store_failure(&event, &error);
+        }
+        for listener in &self.listeners {
+            listener.on_event(&event);
+        }
+    }
+
+    fn record_transaction(
+        &mut self,
+        request: &BeverageRequest,
+        dispense_outcome: &DispenseOutcome,
+    ) {
+        let (outcome, change) = match dispense_outcome {
+            DispenseOutcome::Served { change } => (TransactionOutcome::Served, *change),
+            DispenseOutcome::Failed(DispenseError::Payment(
+                cashier::UnsuccessfulPayment::NotEnoughMoney(_),
+            )) => (TransactionOutcome::Underpaid, 0),
+            DispenseOutcome::Failed(DispenseError::Payment(
+                cashier::UnsuccessfulPayment::ExactChangeOnly,
+            )) => (TransactionOutcome::ExactChangeOnly, 0),
+            DispenseOutcome::Failed(DispenseError::Shortage { .. }) => {
+                (TransactionOutcome::ShortageRefund, 0)
+            }
+            DispenseOutcome::Failed(DispenseError::ServingFailed { .. })
+            | DispenseOutcome::Failed(DispenseError::MachineUnavailable { .. }) => {
+                (TransactionOutcome::ServingFailureRefund, 0)
+            }
+            DispenseOutcome::Failed(DispenseError::Payment(
+                cashier::UnsuccessfulPayment::UnpayableAmount(_),
+            ))
+            | DispenseOutcome::Failed(DispenseError::InvalidRequest(_)) => {
+                (TransactionOutcome::Rejected, 0)
+            }
+        };
+        let price_charged = match outcome {
+            TransactionOutcome::Served => request.money_amount - change,
+            _ => 0,
+        };
+
+        let entry = LedgerEntry {
+            beverage: request.beverage.clone(),
+            sugar_amount: request.sugar_amount.clone(),
+            cup_size: request.cup_size,
+            add_ons: AddOn::distinct(request.add_ons).collect(),
+            money_inserted: request.money_amount,
+            price_charged,
+            change,
+            outcome,
+            occurred_at: self.clock.now(),
+        };
+        self.sales_ledger.record(entry.clone());
+        self.record_event(MachineEvent::TransactionRecorded(entry));
+    }
+
+    fn reject_payment(
+        &self,
+        unsuccessful_payment: cashier::UnsuccessfulPayment,
+    ) -> DispenseOutcome {
+        self.handle_unsuccessful_payment(&unsuccessful_payment);
+        DispenseOutcome::Failed(DispenseError::Payment(unsuccessful_payment))
+    }
+
+    fn handle_unsuccessful_payment(&self, unsuccessful_payment: &cashier::UnsuccessfulPayment) {
+        match unsuccessful_payment {
+            cashier::UnsuccessfulPayment::NotEnoughMoney(missing_money_amount) => self
                 .display
-                .show_missing_money_message(missing_money_amount),
+                .show_missing_money_message(*missing_money_amount),
+            cashier::UnsuccessfulPayment::ExactChangeOnly => {
+                self.display.show_exact_change_only_message()
+            }
+            cashier::UnsuccessfulPayment::UnpayableAmount(amount) => {
+                self.display.show_unpayable_amount_message(*amount)
+            }
+        }
+    }
+
+    fn handle_dispensed(
+        &mut self,
+        request: &BeverageRequest,
+        payment: Payment,
+        dispensed: dispenser::BeverageDispsense,
+    ) -> DispenseOutcome {
+        let BeverageRequest {
+            beverage,
+            sugar_amount,
+            ref cup_size,
+            add_ons,
+            ..
+        } = *request;
+        match dispensed {
+            dispenser::BeverageDispsense::Ok => {
+                self.beverages_in_shortage
+                    .remove(&(beverage.clone(), *cup_size));
+                self.record_event(MachineEvent::BeverageServed {
+                    beverage: beverage.clone(),
+                    sugar_amount: sugar_amount.clone(),
+                    cup_size: *cup_size,
+                    add_ons: AddOn::distinct(add_ons).collect(),
+                    occurred_at: self.clock.now(),
+                });
+                self.low_stock_monitor.check(
+                    self.dispenser.beverage_quantity_checker(),
+                    self.notifier.as_ref(),
+                );
+                let change = payment.change_amount();
+                if change > 0 {
+                    self.display.show_returned_change_message(change)
+                }
+                DispenseOutcome::Served { change }
+            }
+            dispenser::BeverageDispsense::Shortage { missing_ingredient } => {
+                self.handle_beverage_shortage(beverage, cup_size, missing_ingredient, &payment);
+                DispenseOutcome::Failed(DispenseError::Shortage {
+                    refunded: payment.paid_amount(),
+                    refunded_coins: payment.inserted_coins().to_vec(),
+                })
+            }
+            dispenser::BeverageDispsense::ServingFailed(serving_failure) => {
+                self.handle_serving_failure(beverage, &payment, &serving_failure);
+                let refunded = payment.paid_amount();
+                let refunded_coins = payment.inserted_coins().to_vec();
+                DispenseOutcome::Failed(match serving_failure {
+                    ServingFailure::Unreachable => DispenseError::MachineUnavailable {
+                        refunded,
+                        refunded_coins,
+                    },
+                    failure => DispenseError::ServingFailed {
+                        failure,
+                        refunded,
+                        refunded_coins,
+                    },
+                })
+            }
         }
     }
 
-    fn handle_dispense(&mut self, beverage: &Beverage, sugar_amount: &SugarAmount) {
-        let dispensed = self.dispenser.dispense(beverage, sugar_amount);
+    fn handle_serving_failure(
+        &mut self,
+        beverage: &Beverage,
+        payment: &Payment,
+        serving_failure: &ServingFailure,
+    ) {
+        self.refund_beverage_payment(payment);
+        self.notifier
+            .notify_serving_failure(beverage, serving_failure);
+        self.display.show_serving_failure_message(beverage)
+    }
 
-        if let dispenser::BeverageDispsense::Shortage = dispensed {
-            self.handle_beverage_shortage(beverage)
+    fn handle_beverage_shortage(
+        &mut self,
+        beverage: &Beverage,
+        cup_size: &CupSize,
+        missing_ingredient: Option<Ingredient>,
+        payment: &Payment,
+    ) {
+        let detected_at = self.clock.now();
+        let beverage_is_empty = self.dispenser.beverage_quantity_checker().is_empty(
+            beverage,
+            &SugarAmount::Zero,
+            cup_size,
+            &[],
+        );
+        if beverage_is_empty {
+            self.beverages_in_shortage
+                .insert((beverage.clone(), *cup_size));
+            self.record_event(MachineEvent::ShortageDetected {
+                beverage: beverage.clone(),
+                cup_size: *cup_size,
+                occurred_at: detected_at,
+            });
+        }
+        self.refund_beverage_payment(payment);
+        // Only a missing sweetening or add-on ingredient leaves the plain
+        // beverage servable; checkers without ingredients report the beverage.
+        match missing_ingredient.filter(|_| !beverage_is_empty) {
+            Some(ingredient) => {
+                self.notifier
+                    .notify_missing_ingredient(&ingredient, detected_at);
+                self.display.show_missing_ingredient_message(&ingredient)
+            }
+            None => {
+                self.notifier.notify_missing_beverage(beverage, detected_at);
+                self.display.show_beverage_shortage_message(beverage)
+            }
         }
     }
 
-    fn handle_beverage_shortage(&mut self, beverage: &Beverage) {
-        self.cashier.refund_beverage_payment(beverage);
-        self.notifier.notify_missing_beverage(beverage);
-        self.display.show_beverage_shortage_message(beverage)
+    fn refund_beverage_payment(&mut self, payment: &Payment) {
+        let refunded_at = self.clock.now();
+        self.cashier.refund_beverage_payment(payment, refunded_at);
+        self.record_event(MachineEvent::PaymentRefunded {
+            inserted_coins: payment.inserted_coins().to_vec(),
+            change: payment.change().to_vec(),
+            occurred_at: refunded_at,
+        });
     }
 
     pub fn print_purchases_report(&self) {
-        let dispensed_beverages_history = self.dispenser.dispensed_beverages();
-        let total_money_earned = self.cashier.total_money_earned();
+        let printed_at = self.clock.now();
+        self.record_event(MachineEvent::PurchasesReportPrinted {
+            occurred_at: printed_at,
+        });
+        self.reports_printer
+            .print(self.current_period_report(printed_at))
+    }
+
+    pub fn print_z_report(&mut self) {
+        let period_ended_at = self.clock.now();
+        let purchase_report = self.close_period(period_ended_at);
+        self.record_event(MachineEvent::ZReportPrinted {
+            occurred_at: period_ended_at,
+        });
 
-        let purchase_report =
-            PurchasesReport::new(&dispensed_beverages_history.quantities, total_money_earned);
         self.reports_printer.print(purchase_report)
     }
+
+    fn close_period(&mut self, period_ended_at: SystemTime) -> PurchasesReport {
+        let purchase_report = self.current_period_report(period_ended_at);
+
+        self.period_first_entry = self.sales_ledger.entries().len();
+        self.period_started_at = period_ended_at;
+        self.closed_periods.push(purchase_report.clone());
+
+        purchase_report
+    }
+
+    pub fn closed_periods(&self) -> &[PurchasesReport] {
+        &self.closed_periods
+    }
+
+    fn current_period_report(&self, period_ended_at: SystemTime) -> PurchasesReport {
+        PurchasesReport::from_ledger_entries(
+            self.sales_ledger.entries_since(self.period_first_entry),
+            self.period_started_at,
+            period_ended_at,
+        )
+    }
+
+    pub fn restock(&mut self, operator: &str, ingredient: Ingredient, quantity: u32) {
+        let restock_record = RestockRecord {
+            operator: String::from(operator),
+            ingredient,
+            quantity,
+            restocked_at: self.clock.now(),
+        };
+        self.apply_restock(restock_record.clone());
+        self.record_event(MachineEvent::Restocked(restock_record));
+    }
+
+    fn apply_restock(&mut self, restock_record: RestockRecord) {
+        let beverage_quantity_checker = self.dispenser.beverage_quantity_checker();
+        beverage_quantity_checker.refill(restock_record.ingredient, restock_record.quantity);
+        self.beverages_in_shortage.retain(|(beverage, cup_size)| {
+            beverage_quantity_checker.is_empty(beverage, &SugarAmount::Zero, cup_size, &[])
+        });
+
+        self.restock_history.record_restock(restock_record);
+    }
+
+    pub fn beverages_in_shortage(&self) -> &HashSet<(Beverage, CupSize)> {
+        &self.beverages_in_shortage
+    }
+
+    pub fn restock_history(&self) -> &[RestockRecord] {
+        &self.restock_history.records
+    }
+
+    pub fn print_restock_report(&self) {
+        let restock_report = RestockReport::new(&self.restock_history.records);
+        self.reports_printer.print_restock_report(restock_report)
+    }
+
+    pub fn sales_ledger(&self) -> &SalesLedger {
+        &self.sales_ledger
+    }
+
+    pub fn dispensed_beverages_history(&self) -> DispensedBeveragesHistory {
+        DispensedBeveragesHistory::from_ledger(self.sales_ledger.entries())
+    }
+
+    pub fn dispensed_beverages(&self) -> Vec<DispensedBeverage> {
+        self.dispensed_beverages_history().dispensed
+    }
+
+    pub fn refunds(&self) -> &[Refund] {
+        self.cashier.refunds()
+    }
+
+    pub fn snapshot(&self) -> MachineState {
+        let beverage_quantity_checker = self.dispenser.beverage_quantity_checker();
+        let stock: HashMap<Ingredient, u32> = Ingredient::ALL
+            .into_iter()
+            .filter_map(|ingredient| {
+                beverage_quantity_checker
+                    .remaining_stock(&ingredient)
+                    .map(|quantity| (ingredient, quantity))
+            })
+            .collect();
+
+        MachineState {
+            coin_inventory: self.cashier.coin_inventory().clone(),
+            credit: self.cashier.pending_credit().to_vec(),
+            ledger_entries: self.sales_ledger.entries().to_vec(),
+            period_first_entry: self.period_first_entry,
+            period_started_at: self.period_started_at,
+            stock,
+            beverages_in_shortage: self.beverages_in_shortage.clone(),
+        }
+    }
+
+    pub(crate) fn restore(&mut self, machine_state: MachineState) {
+        self.cashier
+            .restore(machine_state.coin_inventory, machine_state.credit);
+        self.sales_ledger = SalesLedger::restore(machine_state.ledger_entries);
+        self.period_first_entry = machine_state.period_first_entry;
+        self.period_started_at = machine_state.period_started_at;
+        let beverage_quantity_checker = self.dispenser.beverage_quantity_checker();
+        for (ingredient, quantity) in machine_state.stock {
+            beverage_quantity_checker.restore_stock(ingredient, quantity);
+        }
+        self.beverages_in_shortage = machine_state.beverages_in_shortage;
+    }
+
+    pub(crate) fn replay(&mut self, event: &MachineEvent) {
+        match event {
+            MachineEvent::CoinInserted { coin, .. } => self.cashier.insert_coin(*coin),
+            MachineEvent::CreditCancelled { .. } => {
+                self.cashier.cancel_credit();
+            }
+            MachineEvent::PaymentAccepted {
+                inserted_coins,
+                change,
+                paid_with_credit,
+                ..
+            } => self.cashier.replay_payment(
+                &Payment::new(inserted_coins.clone(), change.clone()),
+                *paid_with_credit,
+            ),
+            MachineEvent::BeverageServed {
+                beverage,
+                sugar_amount,
+                cup_size,
+                add_ons,
+                ..
+            } => {
+                self.beverages_in_shortage
+                    .remove(&(beverage.clone(), *cup_size));
+                self.dispenser.beverage_quantity_checker().consume(
+                    beverage,
+                    sugar_amount,
+                    cup_size,
+                    add_ons,
+                )
+            }
+            MachineEvent::ShortageDetected {
+                beverage, cup_size, ..
+            } => {
+                self.beverages_in_shortage
+                    .insert((beverage.clone(), *cup_size));
+            }
+            MachineEvent::PaymentRefunded {
+                inserted_coins,
+                change,
+                occurred_at,
+            } => self.cashier.refund_beverage_payment(
+                &Payment::new(inserted_coins.clone(), change.clone()),
+                *occurred_at,
+            ),
+            MachineEvent::TransactionRecorded(entry) => self.sales_ledger.record(entry.clone()),
+            MachineEvent::PurchasesReportPrinted { .. } => {}
+            MachineEvent::ZReportPrinted { occurred_at } => {
+                self.close_period(*occurred_at);
+            }
+            MachineEvent::Restocked(restock_record) => self.apply_restock(restock_record.clone()),
+        }
+    }
+
+    pub fn update_price_list(&mut self, price_list: PriceList) {
+        self.cashier
+            .update_price_list(self.beverage_catalog.price_list().overridden_by(price_list));
+    }
+
+    pub fn coin_inventory(&self) -> &CoinInventory {
+        self.cashier.coin_inventory()
+    }
+
+    pub fn is_exact_change_only(&self) -> bool {
+        self.cashier.is_exact_change_only()
+    }
 }
 
 #[cfg(test)]
 pub(crate) mod machine_tests {
-    use std::cell::RefCell;
     use std::collections::{HashMap, HashSet};
-    use std::rc::Rc;
+    use std::io;
+    use std::sync::Mutex;
+    use std::time::{Duration, SystemTime};
     use test_case::test_case;
 
     use crate::machine_system::beverages::{
-        beverage::Beverage, beverage::HotBeverageOption,
-        beverage_quantity_checker::BeverageQuantityChecker, beverage_request::BeverageRequest,
-        beverage_server::BeverageServer, sugar_amount::SugarAmount,
+        add_on::AddOn,
+        beverage::Beverage,
+        beverage::{ColdBeverageOption, HotBeverageOption},
+        beverage_catalog::{BeverageCatalog, BeverageDefinition, Temperature},
+        beverage_quantity_checker::BeverageQuantityChecker,
+        beverage_request::BeverageRequest,
+        beverage_server::{BeverageServer, ServingFailure},
+        cup_size::CupSize,
+        dispenser::DispensedBeverage,
+        ingredient::{Ingredient, Recipe},
+        ingredient_inventory::IngredientInventory,
+        sugar_amount::{SugarAmount, Sweetener},
+    };
+    use crate::machine_system::cashier::{Refund, UnsuccessfulPayment};
+    use crate::machine_system::clock::ManualClock;
+    use crate::machine_system::coins::{Coin, CoinInventory};
+    use crate::machine_system::dispense_outcome::{
+        DispenseError, DispenseOutcome, InvalidBeverageRequest,
     };
     use crate::machine_system::display::Display;
+    use crate::machine_system::event_store::{EventStore, InMemoryEventStore};
+    use crate::machine_system::low_stock::LowStockThresholds;
     use crate::machine_system::machine_builder::MachineBuilder;
+    use crate::machine_system::machine_event::MachineEvent;
+    use crate::machine_system::machine_event_listener::MachineEventListener;
     use crate::machine_system::notifier::Notifier;
-    use crate::machine_system::reports_printer::{PurchasesReport, ReportsPrinter};
+    use crate::machine_system::price_list::PriceList;
+    use crate::machine_system::reports_printer::{PurchasesReport, ReportsPrinter, RestockReport};
+    use crate::machine_system::sales_ledger::{LedgerEntry, TransactionOutcome};
+    use crate::shared::Shared;
 
     const ENOUGH_MONEY: u32 = 100;
 
+    struct MachineEventListenerSpy {
+        received_events: Shared<Mutex<Vec<MachineEvent>>>,
+    }
+
+    impl MachineEventListener for MachineEventListenerSpy {
+        fn on_event(&self, event: &MachineEvent) {
+            self.received_events.lock().unwrap().push(event.clone())
+        }
+    }
+
     pub(crate) struct DummyBeverageServer {}
     impl BeverageServer for DummyBeverageServer {
-        fn serve(&self, _beverage: &Beverage, _sugar_amount: &SugarAmount) {}
+        fn serve(
+            &self,
+            _beverage: &Beverage,
+            _sugar_amount: &SugarAmount,
+            _cup_size: &CupSize,
+            _add_ons: &[AddOn],
+        ) -> Result<(), ServingFailure> {
+            Ok(())
+        }
+    }
+
+    struct FailingBeverageServerStub {
+        serving_failure: ServingFailure,
+    }
+    impl BeverageServer for FailingBeverageServerStub {
+        fn serve(
+            &self,
+            _beverage: &Beverage,
+            _sugar_amount: &SugarAmount,
+            _cup_size: &CupSize,
+            _add_ons: &[AddOn],
+        ) -> Result<(), ServingFailure> {
+            Err(self.serving_failure.clone())
+        }
     }
 
     struct BeverageServerTestDouble {
-        requested_beverages: RefCell<Vec<(Beverage, SugarAmount)>>,
+        requested_beverages: Mutex<Vec<(Beverage, SugarAmount)>>,
+        requested_cup_sizes: Mutex<Vec<CupSize>>,
+        requested_add_ons: Mutex<Vec<Vec<AddOn>>>,
     }
 
     impl BeverageServerTestDouble {
         fn new() -> Self {
             Self {
-                requested_beverages: RefCell::new(Vec::new()),
+                requested_beverages: Mutex::new(Vec::new()),
+                requested_cup_sizes: Mutex::new(Vec::new()),
+                requested_add_ons: Mutex::new(Vec::new()),
             }
         }
 
         fn spied_requested_beverages(&self) -> Vec<(Beverage, SugarAmount)> {
-            self.requested_beverages.borrow().clone()
+            self.requested_beverages.lock().unwrap().clone()
+        }
+
+        fn spied_requested_cup_sizes(&self) -> Vec<CupSize> {
+            self.requested_cup_sizes.lock().unwrap().clone()
+        }
+
+        fn spied_requested_add_ons(&self) -> Vec<Vec<AddOn>> {
+            self.requested_add_ons.lock().unwrap().clone()
         }
     }
 
     impl BeverageServer for BeverageServerTestDouble {
-        fn serve(&self, beverage: &Beverage, sugar_amount: &SugarAmount) {
+        fn serve(
+            &self,
+            beverage: &Beverage,
+            sugar_amount: &SugarAmount,
+            cup_size: &CupSize,
+            add_ons: &[AddOn],
+        ) -> Result<(), ServingFailure> {
             self.requested_beverages
-                .borrow_mut()
+                .lock()
+                .unwrap()
                 .push((beverage.clone(), sugar_amount.clone()));
+            self.requested_cup_sizes.lock().unwrap().push(*cup_size);
+            self.requested_add_ons
+                .lock()
+                .unwrap()
+                .push(add_ons.to_vec());
+            Ok(())
         }
     }
 
     pub(crate) struct InfiniteBeverageQuantityCheckerFake {}
     impl BeverageQuantityChecker for InfiniteBeverageQuantityCheckerFake {
-        fn is_empty(&self, _beverage: &Beverage) -> bool {
+        fn is_empty(
+            &self,
+            _beverage: &Beverage,
+            _sugar_amount: &SugarAmount,
+            _cup_size: &CupSize,
+            _add_ons: &[AddOn],
+        ) -> bool {
             false
         }
     }
 
     struct EmptyBeverageQuantityCheckerFake {}
     impl BeverageQuantityChecker for EmptyBeverageQuantityCheckerFake {
-        fn is_empty(&self, _beverage: &Beverage) -> bool {
+        fn is_empty(
+            &self,
+            _beverage: &Beverage,
+            _sugar_amount: &SugarAmount,
+            _cup_size: &CupSize,
+            _add_ons: &[AddOn],
+        ) -> bool {
             true
         }
     }
 
     struct BeverageQuantityCheckerTestDouble {
-        empty_beverages: RefCell<HashSet<Beverage>>,
+        empty_beverages: Mutex<HashSet<Beverage>>,
     }
 
     impl BeverageQuantityCheckerTestDouble {
         fn new() -> Self {
             BeverageQuantityCheckerTestDouble {
-                empty_beverages: RefCell::new(HashSet::new()),
+                empty_beverages: Mutex::new(HashSet::new()),
             }
         }
 
-        fn stub_beverage_as_available(&self, _beverage: &Beverage) {}
+        fn stub_beverage_as_available(&self, beverage: &Beverage) {
+            self.empty_beverages.lock().unwrap().remove(beverage);
+        }
 
         fn stub_beverage_as_empty(&self, beverage: Beverage) {
-            self.empty_beverages.borrow_mut().insert(beverage);
+            self.empty_beverages.lock().unwrap().insert(beverage);
         }
     }
 
     impl BeverageQuantityChecker for BeverageQuantityCheckerTestDouble {
-        fn is_empty(&self, beverage: &Beverage) -> bool {
-            self.empty_beverages.borrow().contains(beverage)
+        fn is_empty(
+            &self,
+            beverage: &Beverage,
+            _sugar_amount: &SugarAmount,
+            _cup_size: &CupSize,
+            _add_ons: &[AddOn],
+        ) -> bool {
+            self.empty_beverages.lock().unwrap().contains(beverage)
         }
     }
 
@@ -150,99 +849,304 @@ pub(crate) mod machine_tests {
         fn show_missing_money_message(&self, _missing_money: u32) {}
 
         fn show_beverage_shortage_message(&self, _beverage: &Beverage) {}
+
+        fn show_missing_ingredient_message(&self, _ingredient: &Ingredient) {}
+
+        fn show_serving_failure_message(&self, _beverage: &Beverage) {}
+
+        fn show_returned_change_message(&self, _change: u32) {}
+
+        fn show_exact_change_only_message(&self) {}
+
+        fn show_unpayable_amount_message(&self, _amount: u32) {}
+
+        fn show_credit_message(&self, _credit: u32) {}
     }
 
     struct DisplayTestDouble {
-        missing_money_message_requests: RefCell<Vec<u32>>,
-        beverage_shortage_message_request: RefCell<Vec<Beverage>>,
+        missing_money_message_requests: Mutex<Vec<u32>>,
+        beverage_shortage_message_request: Mutex<Vec<Beverage>>,
+        missing_ingredient_message_requests: Mutex<Vec<Ingredient>>,
+        serving_failure_message_requests: Mutex<Vec<Beverage>>,
+        returned_change_message_requests: Mutex<Vec<u32>>,
+        exact_change_only_message_requests: Mutex<u32>,
+        unpayable_amount_message_requests: Mutex<Vec<u32>>,
+        credit_message_requests: Mutex<Vec<u32>>,
     }
 
     impl DisplayTestDouble {
         fn new() -> Self {
             Self {
-                missing_money_message_requests: RefCell::new(Vec::new()),
-                beverage_shortage_message_request: RefCell::new(Vec::new()),
+                missing_money_message_requests: Mutex::new(Vec::new()),
+                beverage_shortage_message_request: Mutex::new(Vec::new()),
+                missing_ingredient_message_requests: Mutex::new(Vec::new()),
+                serving_failure_message_requests: Mutex::new(Vec::new()),
+                returned_change_message_requests: Mutex::new(Vec::new()),
+                exact_change_only_message_requests: Mutex::new(0),
+                unpayable_amount_message_requests: Mutex::new(Vec::new()),
+                credit_message_requests: Mutex::new(Vec::new()),
             }
         }
 
         fn spied_missing_money_message_requests(&self) -> Vec<u32> {
-            self.missing_money_message_requests.borrow().clone()
+            self.missing_money_message_requests.lock().unwrap().clone()
         }
 
         fn spied_beverage_shortage_message_requests(&self) -> Vec<Beverage> {
-            self.beverage_shortage_message_request.borrow().clone()
+            self.beverage_shortage_message_request
+                .lock()
+                .unwrap()
+                .clone()
+        }
+
+        fn spied_missing_ingredient_message_requests(&self) -> Vec<Ingredient> {
+            self.missing_ingredient_message_requests
+                .lock()
+                .unwrap()
+                .clone()
+        }
+
+        fn spied_serving_failure_message_requests(&self) -> Vec<Beverage> {
+            self.serving_failure_message_requests
+                .lock()
+                .unwrap()
+                .clone()
+        }
+
+        fn spied_returned_change_message_requests(&self) -> Vec<u32> {
+            self.returned_change_message_requests
+                .lock()
+                .unwrap()
+                .clone()
+        }
+
+        fn spied_exact_change_only_message_requests(&self) -> u32 {
+            *self.exact_change_only_message_requests.lock().unwrap()
+        }
+
+        fn spied_unpayable_amount_message_requests(&self) -> Vec<u32> {
+            self.unpayable_amount_message_requests
+                .lock()
+                .unwrap()
+                .clone()
+        }
+
+        fn spied_credit_message_requests(&self) -> Vec<u32> {
+            self.credit_message_requests.lock().unwrap().clone()
         }
     }
 
     impl Display for DisplayTestDouble {
         fn show_missing_money_message(&self, missing_money: u32) {
             self.missing_money_message_requests
-                .borrow_mut()
+                .lock()
+                .unwrap()
                 .push(missing_money);
         }
 
         fn show_beverage_shortage_message(&self, beverage: &Beverage) {
             self.beverage_shortage_message_request
-                .borrow_mut()
+                .lock()
+                .unwrap()
+                .push(beverage.clone());
+        }
+
+        fn show_missing_ingredient_message(&self, ingredient: &Ingredient) {
+            self.missing_ingredient_message_requests
+                .lock()
+                .unwrap()
+                .push(*ingredient);
+        }
+
+        fn show_serving_failure_message(&self, beverage: &Beverage) {
+            self.serving_failure_message_requests
+                .lock()
+                .unwrap()
                 .push(beverage.clone());
         }
+
+        fn show_returned_change_message(&self, change: u32) {
+            self.returned_change_message_requests
+                .lock()
+                .unwrap()
+                .push(change);
+        }
+
+        fn show_exact_change_only_message(&self) {
+            *self.exact_change_only_message_requests.lock().unwrap() += 1;
+        }
+
+        fn show_unpayable_amount_message(&self, amount: u32) {
+            self.unpayable_amount_message_requests
+                .lock()
+                .unwrap()
+                .push(amount);
+        }
+
+        fn show_credit_message(&self, credit: u32) {
+            self.credit_message_requests.lock().unwrap().push(credit);
+        }
     }
 
     pub(crate) struct DummyReportsPrinter {}
     impl ReportsPrinter for DummyReportsPrinter {
         fn print(&self, _purchase_report: PurchasesReport) {}
+
+        fn print_restock_report(&self, _restock_report: RestockReport) {}
     }
 
     struct ReportsPrinterTestDouble {
-        reports_requested_to_print: RefCell<Vec<PurchasesReport>>,
+        reports_requested_to_print: Mutex<Vec<PurchasesReport>>,
+        restock_reports_requested_to_print: Mutex<Vec<RestockReport>>,
     }
 
     impl ReportsPrinterTestDouble {
         fn new() -> Self {
             Self {
-                reports_requested_to_print: RefCell::new(vec![]),
+                reports_requested_to_print: Mutex::new(vec![]),
+                restock_reports_requested_to_print: Mutex::new(vec![]),
             }
         }
 
         fn spied_reports_requested_to_print(&self) -> Vec<PurchasesReport> {
-            self.reports_requested_to_print.borrow().clone()
+            self.reports_requested_to_print.lock().unwrap().clone()
+        }
+
+        fn spied_restock_reports_requested_to_print(&self) -> Vec<RestockReport> {
+            self.restock_reports_requested_to_print
+                .lock()
+                .unwrap()
+                .clone()
         }
     }
 
     impl ReportsPrinter for ReportsPrinterTestDouble {
         fn print(&self, purchase_report: PurchasesReport) {
             self.reports_requested_to_print
-                .borrow_mut()
+                .lock()
+                .unwrap()
                 .push(purchase_report.clone())
         }
+
+        fn print_restock_report(&self, restock_report: RestockReport) {
+            self.restock_reports_requested_to_print
+                .lock()
+                .unwrap()
+                .push(restock_report)
+        }
     }
 
     pub(crate) struct DummyNotifier {}
     impl Notifier for DummyNotifier {
-        fn notify_missing_beverage(&self, _drink: &Beverage) {}
+        fn notify_missing_beverage(&self, _drink: &Beverage, _detected_at: SystemTime) {}
+
+        fn notify_missing_ingredient(&self, _ingredient: &Ingredient, _detected_at: SystemTime) {}
+
+        fn notify_serving_failure(&self, _beverage: &Beverage, _serving_failure: &ServingFailure) {}
+
+        fn notify_low_stock(&self, _beverage: &Beverage, _remaining_servings: u32) {}
+
+        fn notify_low_ingredient_stock(&self, _ingredient: &Ingredient, _remaining_quantity: u32) {}
+
+        fn notify_event_store_failure(&self, _event: &MachineEvent, _error: &io::Error) {}
     }
 
     struct NotifierTestDouble {
-        missing_beverages_notifications: RefCell<Vec<Beverage>>,
+        missing_beverages_notifications: Mutex<Vec<(Beverage, SystemTime)>>,
+        missing_ingredients_notifications: Mutex<Vec<(Ingredient, SystemTime)>>,
+        serving_failures_notifications: Mutex<Vec<(Beverage, ServingFailure)>>,
+        low_stock_notifications: Mutex<Vec<(Beverage, u32)>>,
+        low_ingredient_stock_notifications: Mutex<Vec<(Ingredient, u32)>>,
+        event_store_failures_notifications: Mutex<Vec<(MachineEvent, io::ErrorKind)>>,
     }
 
     impl NotifierTestDouble {
         fn new() -> Self {
             Self {
-                missing_beverages_notifications: RefCell::new(Vec::new()),
+                missing_beverages_notifications: Mutex::new(Vec::new()),
+                missing_ingredients_notifications: Mutex::new(Vec::new()),
+                serving_failures_notifications: Mutex::new(Vec::new()),
+                low_stock_notifications: Mutex::new(Vec::new()),
+                low_ingredient_stock_notifications: Mutex::new(Vec::new()),
+                event_store_failures_notifications: Mutex::new(Vec::new()),
             }
         }
 
-        fn spied_missing_beverages_notifications(&self) -> Vec<Beverage> {
-            self.missing_beverages_notifications.borrow().clone()
+        fn spied_missing_beverages_notifications(&self) -> Vec<(Beverage, SystemTime)> {
+            self.missing_beverages_notifications.lock().unwrap().clone()
+        }
+
+        fn spied_missing_ingredients_notifications(&self) -> Vec<(Ingredient, SystemTime)> {
+            self.missing_ingredients_notifications
+                .lock()
+                .unwrap()
+                .clone()
+        }
+
+        fn spied_serving_failures_notifications(&self) -> Vec<(Beverage, ServingFailure)> {
+            self.serving_failures_notifications.lock().unwrap().clone()
+        }
+
+        fn spied_low_stock_notifications(&self) -> Vec<(Beverage, u32)> {
+            self.low_stock_notifications.lock().unwrap().clone()
+        }
+
+        fn spied_low_ingredient_stock_notifications(&self) -> Vec<(Ingredient, u32)> {
+            self.low_ingredient_stock_notifications
+                .lock()
+                .unwrap()
+                .clone()
+        }
+
+        fn spied_event_store_failures_notifications(&self) -> Vec<(MachineEvent, io::ErrorKind)> {
+            self.event_store_failures_notifications
+                .lock()
+                .unwrap()
+                .clone()
         }
     }
 
     impl Notifier for NotifierTestDouble {
-        fn notify_missing_beverage(&self, drink: &Beverage) {
+        fn notify_missing_beverage(&self, drink: &Beverage, detected_at: SystemTime) {
             self.missing_beverages_notifications
-                .borrow_mut()
-                .push(drink.clone())
+                .lock()
+                .unwrap()
+                .push((drink.clone(), detected_at))
+        }
+
+        fn notify_missing_ingredient(&self, ingredient: &Ingredient, detected_at: SystemTime) {
+            self.missing_ingredients_notifications
+                .lock()
+                .unwrap()
+                .push((*ingredient, detected_at))
+        }
+
+        fn notify_serving_failure(&self, beverage: &Beverage, serving_failure: &ServingFailure) {
+            self.serving_failures_notifications
+                .lock()
+                .unwrap()
+                .push((beverage.clone(), serving_failure.clone()))
+        }
+
+        fn notify_low_stock(&self, beverage: &Beverage, remaining_servings: u32) {
+            self.low_stock_notifications
+                .lock()
+                .unwrap()
+                .push((beverage.clone(), remaining_servings))
+        }
+
+        fn notify_low_ingredient_stock(&self, ingredient: &Ingredient, remaining_quantity: u32) {
+            self.low_ingredient_stock_notifications
+                .lock()
+                .unwrap()
+                .push((*ingredient, remaining_quantity))
+        }
+
+        fn notify_event_store_failure(&self, event: &MachineEvent, error: &io::Error) {
+            self.event_store_failures_notifications
+                .lock()
+                .unwrap()
+                .push((event.clone(), error.kind()))
         }
     }
 
@@ -254,13 +1158,13 @@ pub(crate) mod machine_tests {
     #[test_case(Beverage::HotChocolate(HotBeverageOption::ExtraHot) ; "extra hot hot chocolate")]
     #[test_case(Beverage::OrangeJuice; "Orange juice")]
     fn machine_dispenses_beverage_with_no_sugar(beverage: Beverage) {
-        let beverage_server_test_double = Rc::new(BeverageServerTestDouble::new());
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
         let mut machine = MachineBuilder::default()
-            .set(Rc::clone(&beverage_server_test_double))
-            .set(Rc::new(InfiniteBeverageQuantityCheckerFake {}))
-            .set(Rc::new(DummyDisplay {}))
-            .set(Rc::new(DummyReportsPrinter {}))
-            .set(Rc::new(DummyNotifier {}))
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
             .build();
 
         let beverage_request = BeverageRequest::new(&beverage, &SugarAmount::Zero, ENOUGH_MONEY);
@@ -276,13 +1180,13 @@ pub(crate) mod machine_tests {
     #[test_case(SugarAmount::One; "one sugar")]
     #[test_case(SugarAmount::Two; "two sugars")]
     fn machine_dispenses_beverage_with_sugar(sugar_amount: SugarAmount) {
-        let beverage_server_test_double = Rc::new(BeverageServerTestDouble::new());
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
         let mut machine = MachineBuilder::default()
-            .set(Rc::clone(&beverage_server_test_double))
-            .set(Rc::new(InfiniteBeverageQuantityCheckerFake {}))
-            .set(Rc::new(DummyDisplay {}))
-            .set(Rc::new(DummyReportsPrinter {}))
-            .set(Rc::new(DummyNotifier {}))
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
             .build();
 
         let beverage_request = BeverageRequest::new(
@@ -310,13 +1214,13 @@ pub(crate) mod machine_tests {
         beverage: Beverage,
         money_amount: u32,
     ) {
-        let beverage_server_test_double = Rc::new(BeverageServerTestDouble::new());
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
         let mut machine = MachineBuilder::default()
-            .set(Rc::clone(&beverage_server_test_double))
-            .set(Rc::new(InfiniteBeverageQuantityCheckerFake {}))
-            .set(Rc::new(DummyDisplay {}))
-            .set(Rc::new(DummyReportsPrinter {}))
-            .set(Rc::new(DummyNotifier {}))
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
             .build();
 
         let beverage_request = BeverageRequest::new(&beverage, &SugarAmount::Zero, money_amount);
@@ -337,13 +1241,13 @@ pub(crate) mod machine_tests {
         beverage: Beverage,
         money_amount: u32,
     ) {
-        let beverage_server_test_double = Rc::new(BeverageServerTestDouble::new());
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
         let mut machine = MachineBuilder::default()
-            .set(Rc::clone(&beverage_server_test_double))
-            .set(Rc::new(InfiniteBeverageQuantityCheckerFake {}))
-            .set(Rc::new(DummyDisplay {}))
-            .set(Rc::new(DummyReportsPrinter {}))
-            .set(Rc::new(DummyNotifier {}))
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
             .build();
 
         let beverage_request = BeverageRequest::new(&beverage, &SugarAmount::Zero, money_amount);
@@ -366,13 +1270,13 @@ pub(crate) mod machine_tests {
         money_amount: u32,
         missing_money_amount: u32,
     ) {
-        let display_test_double = Rc::new(DisplayTestDouble::new());
+        let display_test_double = Shared::new(DisplayTestDouble::new());
         let mut machine = MachineBuilder::default()
-            .set(Rc::new(DummyBeverageServer {}))
-            .set(Rc::new(InfiniteBeverageQuantityCheckerFake {}))
-            .set(Rc::clone(&display_test_double))
-            .set(Rc::new(DummyReportsPrinter {}))
-            .set(Rc::new(DummyNotifier {}))
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
             .build();
 
         let beverage_request = BeverageRequest::new(&beverage, &SugarAmount::Zero, money_amount);
@@ -388,13 +1292,15 @@ pub(crate) mod machine_tests {
 
     #[test]
     fn machine_prints_purchases_report() {
-        let reports_printer_test_double = Rc::new(ReportsPrinterTestDouble::new());
+        let reports_printer_test_double = Shared::new(ReportsPrinterTestDouble::new());
+        let clock = Shared::new(ManualClock::default());
         let mut machine = MachineBuilder::default()
-            .set(Rc::new(DummyBeverageServer {}))
-            .set(Rc::new(InfiniteBeverageQuantityCheckerFake {}))
-            .set(Rc::new(DummyDisplay {}))
-            .set(Rc::clone(&reports_printer_test_double))
-            .set(Rc::new(DummyNotifier {}))
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::clone(&reports_printer_test_double))
+            .set(Shared::new(DummyNotifier {}))
+            .with_clock(Shared::clone(&clock))
             .build();
         machine.dispense(BeverageRequest::new(
             &Beverage::Coffee(HotBeverageOption::Standard),
@@ -412,19 +1318,30 @@ pub(crate) mod machine_tests {
             ENOUGH_MONEY,
         ));
 
+        clock.advance(Duration::from_secs(60));
         machine.print_purchases_report();
 
         let mut beverages: HashMap<Beverage, u32> = HashMap::new();
         beverages.insert(Beverage::Coffee(HotBeverageOption::Standard), 2);
         beverages.insert(Beverage::OrangeJuice, 1);
+        let printed_reports = reports_printer_test_double.spied_reports_requested_to_print();
         let expeted_report = PurchasesReport {
             beverages_quantities: beverages,
+            beverages_revenues: HashMap::from([
+                (Beverage::Coffee(HotBeverageOption::Standard), 120),
+                (Beverage::OrangeJuice, 60),
+            ]),
             total_money_earned: 180,
+            hot_options_quantities: HashMap::from([(HotBeverageOption::Standard, 2)]),
+            cup_sizes_quantities: HashMap::from([(CupSize::Medium, 3)]),
+            sugar_doses_used: 0,
+            stirring_sticks_used: 0,
+            underpaid_attempts: HashMap::new(),
+            shortage_attempts: HashMap::new(),
+            period_started_at: SystemTime::UNIX_EPOCH,
+            period_ended_at: SystemTime::UNIX_EPOCH + Duration::from_secs(60),
         };
-        assert_eq!(
-            reports_printer_test_double.spied_reports_requested_to_print(),
-            vec![expeted_report]
-        )
+        assert_eq!(printed_reports, vec![expeted_report])
     }
 
     #[test_case(Beverage::Coffee(HotBeverageOption::Standard))]
@@ -432,13 +1349,13 @@ pub(crate) mod machine_tests {
     #[test_case(Beverage::HotChocolate(HotBeverageOption::Standard))]
     #[test_case(Beverage::OrangeJuice)]
     fn machine_shows_shortage_message(beverage: Beverage) {
-        let display_test_double = Rc::new(DisplayTestDouble::new());
+        let display_test_double = Shared::new(DisplayTestDouble::new());
         let mut machine = MachineBuilder::default()
-            .set(Rc::new(DummyBeverageServer {}))
-            .set(Rc::new(EmptyBeverageQuantityCheckerFake {}))
-            .set(Rc::clone(&display_test_double))
-            .set(Rc::new(DummyReportsPrinter {}))
-            .set(Rc::new(DummyNotifier {}))
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(EmptyBeverageQuantityCheckerFake {}))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
             .build();
 
         let beverage_request = BeverageRequest::new(&beverage, &SugarAmount::Zero, ENOUGH_MONEY);
@@ -451,13 +1368,13 @@ pub(crate) mod machine_tests {
 
     #[test]
     fn machine_does_not_dispense_the_requested_beverage_when_there_is_a_shortage() {
-        let beverage_server_test_double = Rc::new(BeverageServerTestDouble::new());
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
         let mut machine = MachineBuilder::default()
-            .set(Rc::clone(&beverage_server_test_double))
-            .set(Rc::new(EmptyBeverageQuantityCheckerFake {}))
-            .set(Rc::new(DummyDisplay {}))
-            .set(Rc::new(DummyReportsPrinter {}))
-            .set(Rc::new(DummyNotifier {}))
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(EmptyBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
             .build();
 
         let beverage_request = BeverageRequest::new(
@@ -473,17 +1390,19 @@ pub(crate) mod machine_tests {
 
     #[test]
     fn purchase_report_does_not_contain_beverages_not_dispensed_due_to_a_shortage() {
-        let reports_printer_test_double = Rc::new(ReportsPrinterTestDouble::new());
-        let stub_beverage_quantity_checker = Rc::new(BeverageQuantityCheckerTestDouble::new());
+        let reports_printer_test_double = Shared::new(ReportsPrinterTestDouble::new());
+        let stub_beverage_quantity_checker = Shared::new(BeverageQuantityCheckerTestDouble::new());
         stub_beverage_quantity_checker
             .stub_beverage_as_available(&Beverage::Coffee(HotBeverageOption::Standard));
         stub_beverage_quantity_checker.stub_beverage_as_empty(Beverage::OrangeJuice);
+        let clock = Shared::new(ManualClock::default());
         let mut machine = MachineBuilder::default()
-            .set(Rc::new(DummyBeverageServer {}))
+            .set(Shared::new(DummyBeverageServer {}))
             .set(stub_beverage_quantity_checker)
-            .set(Rc::new(DummyDisplay {}))
-            .set(Rc::clone(&reports_printer_test_double))
-            .set(Rc::new(DummyNotifier {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::clone(&reports_printer_test_double))
+            .set(Shared::new(DummyNotifier {}))
+            .with_clock(Shared::clone(&clock))
             .build();
         machine.dispense(BeverageRequest::new(
             &Beverage::Coffee(HotBeverageOption::Standard),
@@ -496,35 +1415,48 @@ pub(crate) mod machine_tests {
             ENOUGH_MONEY,
         ));
 
+        clock.advance(Duration::from_secs(60));
         machine.print_purchases_report();
 
         let mut beverages: HashMap<Beverage, u32> = HashMap::new();
         beverages.insert(Beverage::Coffee(HotBeverageOption::Standard), 1);
+        let printed_reports = reports_printer_test_double.spied_reports_requested_to_print();
         let expeted_report = PurchasesReport {
             beverages_quantities: beverages,
+            beverages_revenues: HashMap::from([(
+                Beverage::Coffee(HotBeverageOption::Standard),
+                60,
+            )]),
             total_money_earned: 60,
+            hot_options_quantities: HashMap::from([(HotBeverageOption::Standard, 1)]),
+            cup_sizes_quantities: HashMap::from([(CupSize::Medium, 1)]),
+            sugar_doses_used: 0,
+            stirring_sticks_used: 0,
+            underpaid_attempts: HashMap::new(),
+            shortage_attempts: HashMap::from([(Beverage::OrangeJuice, 1)]),
+            period_started_at: SystemTime::UNIX_EPOCH,
+            period_ended_at: SystemTime::UNIX_EPOCH + Duration::from_secs(60),
         };
-        assert_eq!(
-            reports_printer_test_double.spied_reports_requested_to_print(),
-            vec![expeted_report]
-        )
+        assert_eq!(printed_reports, vec![expeted_report])
     }
 
     #[test]
     fn machine_notifies_when_unable_to_dipsense_beverage_due_to_a_shortage() {
-        let stub_beverage_quantity_checker = Rc::new(BeverageQuantityCheckerTestDouble::new());
+        let stub_beverage_quantity_checker = Shared::new(BeverageQuantityCheckerTestDouble::new());
         stub_beverage_quantity_checker
             .stub_beverage_as_available(&Beverage::Coffee(HotBeverageOption::Standard));
         stub_beverage_quantity_checker.stub_beverage_as_empty(Beverage::OrangeJuice);
         stub_beverage_quantity_checker
             .stub_beverage_as_empty(Beverage::Tea(HotBeverageOption::ExtraHot));
-        let notifier_test_double = Rc::new(NotifierTestDouble::new());
+        let notifier_test_double = Shared::new(NotifierTestDouble::new());
+        let clock = Shared::new(ManualClock::default());
         let mut machine = MachineBuilder::default()
-            .set(Rc::new(DummyBeverageServer {}))
+            .set(Shared::new(DummyBeverageServer {}))
             .set(stub_beverage_quantity_checker)
-            .set(Rc::new(DummyDisplay {}))
-            .set(Rc::new(DummyReportsPrinter {}))
-            .set(Rc::clone(&notifier_test_double))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::clone(&notifier_test_double))
+            .with_clock(Shared::clone(&clock))
             .build();
 
         let coffee_beverage_request = BeverageRequest::new(
@@ -540,7 +1472,9 @@ pub(crate) mod machine_tests {
             ENOUGH_MONEY,
         );
         machine.dispense(coffee_beverage_request);
+        clock.advance(Duration::from_secs(60));
         machine.dispense(orange_juice_beverage_request);
+        clock.advance(Duration::from_secs(60));
         machine.dispense(tea_beverage_request);
 
         let notified_missing_beverages =
@@ -548,9 +1482,1816 @@ pub(crate) mod machine_tests {
         assert_eq!(
             notified_missing_beverages,
             vec![
-                Beverage::OrangeJuice,
-                Beverage::Tea(HotBeverageOption::ExtraHot)
+                (
+                    Beverage::OrangeJuice,
+                    SystemTime::UNIX_EPOCH + Duration::from_secs(60)
+                ),
+                (
+                    Beverage::Tea(HotBeverageOption::ExtraHot),
+                    SystemTime::UNIX_EPOCH + Duration::from_secs(120)
+                )
             ]
         )
     }
+
+    #[test_case(Beverage::Coffee(HotBeverageOption::Standard), 60, 10; "standard coffee, missing 0.1€")]
+    #[test_case(Beverage::Coffee(HotBeverageOption::ExtraHot), 60, 20; "extra hot coffee, missing 0.2€")]
+    fn machine_charges_prices_from_the_configured_price_list(
+        beverage: Beverage,
+        money_amount: u32,
+        missing_money_amount: u32,
+    ) {
+        let display_test_double = Shared::new(DisplayTestDouble::new());
+        let price_list = PriceList::default()
+            .with_price(Beverage::Coffee(HotBeverageOption::Standard), 70)
+            .with_price(Beverage::Coffee(HotBeverageOption::ExtraHot), 80);
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_price_list(price_list)
+            .build();
+
+        let beverage_request = BeverageRequest::new(&beverage, &SugarAmount::Zero, money_amount);
+        machine.dispense(beverage_request);
+
+        let show_missing_money_message_requests =
+            display_test_double.spied_missing_money_message_requests();
+        assert_eq!(
+            show_missing_money_message_requests,
+            vec![missing_money_amount]
+        )
+    }
+
+    #[test_case(Beverage::new("lemonade", None), CupSize::Medium, 30 ; "custom beverage missing from the price list")]
+    #[test_case(Beverage::Coffee(HotBeverageOption::Standard), CupSize::Medium, 20 ; "catalog price of a redefined beverage")]
+    #[test_case(Beverage::Coffee(HotBeverageOption::Standard), CupSize::Large, 0 ; "catalog price with the default cup size adjustment")]
+    #[test_case(Beverage::Tea(HotBeverageOption::Standard), CupSize::Medium, 55 ; "price set on the price list")]
+    fn machine_charges_catalog_prices_for_beverages_missing_from_the_configured_price_list(
+        beverage: Beverage,
+        cup_size: CupSize,
+        expected_change: u32,
+    ) {
+        let default_catalog = BeverageCatalog::default();
+        let coffee = default_catalog
+            .definition_of(&Beverage::Coffee(HotBeverageOption::Standard))
+            .unwrap()
+            .clone();
+        let catalog = default_catalog
+            .with_definition(BeverageDefinition {
+                price: 80,
+                ..coffee
+            })
+            .unwrap()
+            .with_definition(BeverageDefinition {
+                id: String::from("lemonade"),
+                display_name: String::from("lemonade"),
+                drink_maker_code: String::from("L"),
+                price: 70,
+                temperature: Temperature::Cold,
+                allowed_options: vec![],
+                allowed_add_ons: vec![],
+                max_sugar_doses: 0,
+                recipe: Recipe::default().with(Ingredient::WaterMl, 250),
+            })
+            .unwrap();
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_beverage_catalog(catalog)
+            .with_price_list(
+                PriceList::default().with_price(Beverage::Tea(HotBeverageOption::Standard), 45),
+            )
+            .build();
+
+        let outcome = machine.dispense(
+            BeverageRequest::new(&beverage, &SugarAmount::Zero, ENOUGH_MONEY)
+                .with_cup_size(cup_size),
+        );
+
+        assert_eq!(
+            outcome,
+            DispenseOutcome::Served {
+                change: expected_change
+            }
+        )
+    }
+
+    #[test]
+    fn machine_serves_custom_beverages_spelling_a_built_in_one_as_the_built_in_one() {
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let outcome = machine.dispense(BeverageRequest::new(
+            &Beverage::Custom {
+                id: String::from("tea"),
+                option: Some(HotBeverageOption::Standard),
+            },
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert_eq!(outcome, DispenseOutcome::Served { change: 60 });
+        assert_eq!(
+            beverage_server_test_double.spied_requested_beverages(),
+            vec![(
+                Beverage::Tea(HotBeverageOption::Standard),
+                SugarAmount::Zero
+            )]
+        );
+        assert_eq!(
+            machine.sales_ledger().entries()[0].beverage,
+            Beverage::Tea(HotBeverageOption::Standard)
+        )
+    }
+
+    #[test]
+    fn machine_charges_updated_prices_after_a_price_list_update() {
+        let reports_printer_test_double = Shared::new(ReportsPrinterTestDouble::new());
+        let clock = Shared::new(ManualClock::default());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::clone(&reports_printer_test_double))
+            .set(Shared::new(DummyNotifier {}))
+            .with_clock(Shared::clone(&clock))
+            .build();
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        machine.update_price_list(
+            PriceList::default().with_price(Beverage::Tea(HotBeverageOption::Standard), 45),
+        );
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+        clock.advance(Duration::from_secs(60));
+        machine.print_purchases_report();
+
+        let mut beverages: HashMap<Beverage, u32> = HashMap::new();
+        beverages.insert(Beverage::Tea(HotBeverageOption::Standard), 2);
+        let printed_reports = reports_printer_test_double.spied_reports_requested_to_print();
+        let expeted_report = PurchasesReport {
+            beverages_quantities: beverages,
+            beverages_revenues: HashMap::from([(Beverage::Tea(HotBeverageOption::Standard), 85)]),
+            total_money_earned: 85,
+            hot_options_quantities: HashMap::from([(HotBeverageOption::Standard, 2)]),
+            cup_sizes_quantities: HashMap::from([(CupSize::Medium, 2)]),
+            sugar_doses_used: 0,
+            stirring_sticks_used: 0,
+            underpaid_attempts: HashMap::new(),
+            shortage_attempts: HashMap::new(),
+            period_started_at: SystemTime::UNIX_EPOCH,
+            period_ended_at: SystemTime::UNIX_EPOCH + Duration::from_secs(60),
+        };
+        assert_eq!(printed_reports, vec![expeted_report])
+    }
+
+    #[test_case(Beverage::Tea(HotBeverageOption::Standard), 100, 60; "tea costs 0.4€, change 0.6€")]
+    #[test_case(Beverage::Coffee(HotBeverageOption::Standard), 200, 140; "coffee costs 0.6€, change 1.4€")]
+    fn machine_shows_returned_change(beverage: Beverage, money_amount: u32, change: u32) {
+        let display_test_double = Shared::new(DisplayTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let beverage_request = BeverageRequest::new(&beverage, &SugarAmount::Zero, money_amount);
+        machine.dispense(beverage_request);
+
+        assert_eq!(
+            display_test_double.spied_returned_change_message_requests(),
+            vec![change]
+        )
+    }
+
+    #[test]
+    fn machine_does_not_show_change_when_given_the_exact_amount() {
+        let display_test_double = Shared::new(DisplayTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_coin_inventory(CoinInventory::default())
+            .build();
+
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            40,
+        ));
+
+        assert_eq!(
+            display_test_double.spied_returned_change_message_requests(),
+            Vec::<u32>::new()
+        );
+        assert_eq!(machine.coin_inventory().count(&Coin::TwentyCents), 2)
+    }
+
+    #[test]
+    fn machine_does_not_dispense_when_unable_to_give_change() {
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let display_test_double = Shared::new(DisplayTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_coin_inventory(CoinInventory::default())
+            .build();
+
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Coffee(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert!(machine.is_exact_change_only());
+        assert_eq!(
+            display_test_double.spied_exact_change_only_message_requests(),
+            1
+        );
+        assert_eq!(
+            beverage_server_test_double.spied_requested_beverages(),
+            Vec::new()
+        );
+        assert_eq!(machine.coin_inventory(), &CoinInventory::default())
+    }
+
+    #[test]
+    fn machine_rejects_amounts_that_coins_cannot_make() {
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let display_test_double = Shared::new(DisplayTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let dispense_outcome = machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            41,
+        ));
+
+        assert_eq!(
+            dispense_outcome,
+            DispenseOutcome::Failed(DispenseError::Payment(
+                UnsuccessfulPayment::UnpayableAmount(41)
+            ))
+        );
+        assert!(!machine.is_exact_change_only());
+        assert_eq!(
+            display_test_double.spied_unpayable_amount_message_requests(),
+            vec![41]
+        );
+        assert_eq!(
+            display_test_double.spied_exact_change_only_message_requests(),
+            0
+        );
+        assert_eq!(
+            beverage_server_test_double.spied_requested_beverages(),
+            Vec::new()
+        );
+        assert_eq!(
+            machine.sales_ledger().entries()[0].outcome,
+            TransactionOutcome::Rejected
+        )
+    }
+
+    #[test]
+    fn machine_gives_back_the_inserted_coins_when_there_is_a_shortage() {
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(EmptyBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Coffee(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert_eq!(machine.coin_inventory(), &CoinInventory::standard_float())
+    }
+
+    #[test]
+    fn machine_shows_the_credit_accumulated_by_inserted_coins() {
+        let display_test_double = Shared::new(DisplayTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        machine.insert_coin(Coin::TwentyCents);
+        machine.insert_coin(Coin::TenCents);
+        machine.insert_coin(Coin::FiftyCents);
+
+        assert_eq!(machine.credit(), 80);
+        assert_eq!(
+            display_test_double.spied_credit_message_requests(),
+            vec![20, 30, 80]
+        )
+    }
+
+    #[test]
+    fn machine_dispenses_the_selected_beverage_using_the_credit() {
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let display_test_double = Shared::new(DisplayTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        machine.insert_coin(Coin::FiftyCents);
+        machine.insert_coin(Coin::FiftyCents);
+        machine.select_beverage(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::One,
+        );
+
+        assert_eq!(
+            beverage_server_test_double.spied_requested_beverages(),
+            vec![(Beverage::Tea(HotBeverageOption::Standard), SugarAmount::One)]
+        );
+        assert_eq!(
+            display_test_double.spied_returned_change_message_requests(),
+            vec![60]
+        );
+        assert_eq!(machine.credit(), 0)
+    }
+
+    #[test]
+    fn machine_keeps_the_credit_when_it_is_not_enough_for_the_selected_beverage() {
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let display_test_double = Shared::new(DisplayTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        machine.insert_coin(Coin::TwentyCents);
+        machine.select_beverage(
+            &Beverage::Coffee(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+        );
+        machine.insert_coin(Coin::FiftyCents);
+        machine.select_beverage(
+            &Beverage::Coffee(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+        );
+
+        assert_eq!(
+            display_test_double.spied_missing_money_message_requests(),
+            vec![40]
+        );
+        assert_eq!(
+            beverage_server_test_double.spied_requested_beverages(),
+            vec![(
+                Beverage::Coffee(HotBeverageOption::Standard),
+                SugarAmount::Zero
+            )]
+        );
+        assert_eq!(
+            display_test_double.spied_returned_change_message_requests(),
+            vec![10]
+        )
+    }
+
+    #[test]
+    fn machine_gives_back_the_credit_when_cancelled() {
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        machine.insert_coin(Coin::TwentyCents);
+        machine.insert_coin(Coin::OneEuro);
+        let returned_coins = machine.cancel();
+
+        assert_eq!(returned_coins, vec![Coin::TwentyCents, Coin::OneEuro]);
+        assert_eq!(machine.credit(), 0);
+        assert_eq!(machine.coin_inventory(), &CoinInventory::standard_float())
+    }
+
+    #[test]
+    fn machine_gives_back_the_credit_coins_when_the_selected_beverage_is_refunded() {
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(EmptyBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        machine.insert_coin(Coin::TwentyCents);
+        machine.insert_coin(Coin::FiftyCents);
+        let outcome = machine.select_beverage(&Beverage::OrangeJuice, &SugarAmount::Zero);
+
+        assert_eq!(
+            outcome,
+            DispenseOutcome::Failed(DispenseError::Shortage {
+                refunded: 70,
+                refunded_coins: vec![Coin::TwentyCents, Coin::FiftyCents]
+            })
+        );
+        assert_eq!(machine.credit(), 0);
+        assert_eq!(machine.coin_inventory(), &CoinInventory::standard_float())
+    }
+
+    #[test]
+    fn machine_returns_served_outcome_with_the_change() {
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let outcome = machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert_eq!(outcome, DispenseOutcome::Served { change: 60 })
+    }
+
+    #[test]
+    fn machine_returns_not_enough_money_outcome_with_the_missing_amount() {
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let outcome = machine.dispense(BeverageRequest::new(
+            &Beverage::Coffee(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            45,
+        ));
+
+        assert_eq!(
+            outcome,
+            DispenseOutcome::Failed(DispenseError::Payment(UnsuccessfulPayment::NotEnoughMoney(
+                15
+            )))
+        )
+    }
+
+    #[test]
+    fn machine_returns_shortage_outcome_with_the_refunded_amount() {
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(EmptyBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let outcome = machine.dispense(BeverageRequest::new(
+            &Beverage::OrangeJuice,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert_eq!(
+            outcome,
+            DispenseOutcome::Failed(DispenseError::Shortage {
+                refunded: ENOUGH_MONEY,
+                refunded_coins: vec![Coin::OneEuro]
+            })
+        )
+    }
+
+    #[test]
+    fn machine_is_unavailable_when_the_drink_maker_cannot_be_reached() {
+        let notifier_test_double = Shared::new(NotifierTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(FailingBeverageServerStub {
+                serving_failure: ServingFailure::Unreachable,
+            }))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::clone(&notifier_test_double))
+            .build();
+
+        let outcome = machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert_eq!(
+            outcome,
+            DispenseOutcome::Failed(DispenseError::MachineUnavailable {
+                refunded: ENOUGH_MONEY,
+                refunded_coins: vec![Coin::OneEuro]
+            })
+        );
+        assert_eq!(
+            notifier_test_double.spied_serving_failures_notifications(),
+            vec![(
+                Beverage::Tea(HotBeverageOption::Standard),
+                ServingFailure::Unreachable
+            )]
+        );
+        assert_eq!(
+            machine.sales_ledger().entries()[0].outcome,
+            TransactionOutcome::ServingFailureRefund
+        );
+        assert_eq!(machine.coin_inventory(), &CoinInventory::standard_float())
+    }
+
+    #[test]
+    fn machine_refunds_and_notifies_when_the_beverage_cannot_be_served() {
+        let display_test_double = Shared::new(DisplayTestDouble::new());
+        let notifier_test_double = Shared::new(NotifierTestDouble::new());
+        let reports_printer_test_double = Shared::new(ReportsPrinterTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(FailingBeverageServerStub {
+                serving_failure: ServingFailure::Rejected(42),
+            }))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::clone(&reports_printer_test_double))
+            .set(Shared::clone(&notifier_test_double))
+            .build();
+
+        let outcome = machine.dispense(BeverageRequest::new(
+            &Beverage::HotChocolate(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+        machine.print_purchases_report();
+
+        assert_eq!(
+            outcome,
+            DispenseOutcome::Failed(DispenseError::ServingFailed {
+                failure: ServingFailure::Rejected(42),
+                refunded: ENOUGH_MONEY,
+                refunded_coins: vec![Coin::OneEuro]
+            })
+        );
+        assert_eq!(
+            notifier_test_double.spied_serving_failures_notifications(),
+            vec![(
+                Beverage::HotChocolate(HotBeverageOption::Standard),
+                ServingFailure::Rejected(42)
+            )]
+        );
+        assert_eq!(
+            display_test_double.spied_serving_failure_message_requests(),
+            vec![Beverage::HotChocolate(HotBeverageOption::Standard)]
+        );
+        let printed_reports = reports_printer_test_double.spied_reports_requested_to_print();
+        assert_eq!(printed_reports[0].beverages_quantities, HashMap::new());
+        assert_eq!(printed_reports[0].total_money_earned, 0);
+        assert_eq!(machine.coin_inventory(), &CoinInventory::standard_float())
+    }
+
+    #[test]
+    fn machine_detects_a_shortage_of_ice_only_for_beverages_served_with_ice() {
+        let inventory = Shared::new(
+            IngredientInventory::default()
+                .with_stock(Ingredient::OrangeJuiceMl, 1000)
+                .with_stock(Ingredient::IceCubes, 4),
+        );
+        let notifier_test_double = Shared::new(NotifierTestDouble::new());
+        let orange_juice_with_ice = Beverage::cold("orange_juice", ColdBeverageOption::WithIce);
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::clone(&inventory))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::clone(&notifier_test_double))
+            .build();
+
+        let first_outcome = machine.dispense(BeverageRequest::new(
+            &orange_juice_with_ice,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+        let second_outcome = machine.dispense(BeverageRequest::new(
+            &orange_juice_with_ice,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+        let chilled_outcome = machine.dispense(BeverageRequest::new(
+            &Beverage::cold("orange_juice", ColdBeverageOption::Chilled),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert_eq!(first_outcome, DispenseOutcome::Served { change: 40 });
+        assert!(matches!(
+            second_outcome,
+            DispenseOutcome::Failed(DispenseError::Shortage { .. })
+        ));
+        assert_eq!(chilled_outcome, DispenseOutcome::Served { change: 40 });
+        assert_eq!(
+            notifier_test_double
+                .spied_missing_beverages_notifications()
+                .into_iter()
+                .map(|(beverage, _)| beverage)
+                .collect::<Vec<_>>(),
+            vec![orange_juice_with_ice]
+        )
+    }
+
+    #[test]
+    fn machine_notifies_low_stock_once_when_the_threshold_is_crossed() {
+        let inventory = Shared::new(
+            IngredientInventory::default()
+                .with_stock(Ingredient::TeaBags, 4)
+                .with_stock(Ingredient::WaterMl, 10000),
+        );
+        let notifier_test_double = Shared::new(NotifierTestDouble::new());
+        let tea = Beverage::Tea(HotBeverageOption::Standard);
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::clone(&inventory))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::clone(&notifier_test_double))
+            .with_low_stock_thresholds(LowStockThresholds::default().for_beverage(tea.clone(), 2))
+            .build();
+
+        for _ in 0..3 {
+            machine.dispense(BeverageRequest::new(&tea, &SugarAmount::Zero, ENOUGH_MONEY));
+        }
+
+        assert_eq!(
+            notifier_test_double.spied_low_stock_notifications(),
+            vec![(tea, 2)]
+        );
+        assert_eq!(
+            notifier_test_double.spied_missing_beverages_notifications(),
+            Vec::new()
+        )
+    }
+
+    #[test]
+    fn machine_notifies_low_stock_again_after_a_refill() {
+        let inventory = Shared::new(
+            IngredientInventory::default()
+                .with_stock(Ingredient::TeaBags, 2)
+                .with_stock(Ingredient::WaterMl, 10000),
+        );
+        let notifier_test_double = Shared::new(NotifierTestDouble::new());
+        let tea = Beverage::Tea(HotBeverageOption::Standard);
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::clone(&inventory))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::clone(&notifier_test_double))
+            .with_low_stock_thresholds(LowStockThresholds::default().for_beverage(tea.clone(), 1))
+            .build();
+
+        machine.dispense(BeverageRequest::new(&tea, &SugarAmount::Zero, ENOUGH_MONEY));
+        inventory.refill(Ingredient::TeaBags, 5);
+        machine.dispense(BeverageRequest::new(&tea, &SugarAmount::Zero, ENOUGH_MONEY));
+        for _ in 0..5 {
+            machine.dispense(BeverageRequest::new(&tea, &SugarAmount::Zero, ENOUGH_MONEY));
+        }
+
+        assert_eq!(
+            notifier_test_double.spied_low_stock_notifications(),
+            vec![(tea.clone(), 1), (tea, 1)]
+        )
+    }
+
+    #[test]
+    fn machine_notifies_low_ingredient_stock() {
+        let inventory = Shared::new(
+            IngredientInventory::default()
+                .with_stock(Ingredient::CoffeeGrams, 1000)
+                .with_stock(Ingredient::WaterMl, 1000),
+        );
+        let notifier_test_double = Shared::new(NotifierTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::clone(&inventory))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::clone(&notifier_test_double))
+            .with_low_stock_thresholds(
+                LowStockThresholds::default().for_ingredient(Ingredient::WaterMl, 800),
+            )
+            .build();
+
+        for _ in 0..2 {
+            machine.dispense(BeverageRequest::new(
+                &Beverage::Coffee(HotBeverageOption::Standard),
+                &SugarAmount::Zero,
+                ENOUGH_MONEY,
+            ));
+        }
+
+        assert_eq!(
+            notifier_test_double.spied_low_ingredient_stock_notifications(),
+            vec![(Ingredient::WaterMl, 700)]
+        )
+    }
+
+    #[test]
+    fn machine_keeps_refusing_a_beverage_in_shortage_until_it_is_restocked() {
+        let inventory =
+            Shared::new(IngredientInventory::default().with_stock(Ingredient::WaterMl, 10000));
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::clone(&inventory))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+        let tea = Beverage::Tea(HotBeverageOption::Standard);
+
+        machine.dispense(BeverageRequest::new(&tea, &SugarAmount::Zero, ENOUGH_MONEY));
+        assert_eq!(
+            machine.beverages_in_shortage(),
+            &HashSet::from([(tea.clone(), CupSize::Medium)])
+        );
+
+        machine.restock("alice", Ingredient::TeaBags, 10);
+        let outcome =
+            machine.dispense(BeverageRequest::new(&tea, &SugarAmount::Zero, ENOUGH_MONEY));
+
+        assert_eq!(outcome, DispenseOutcome::Served { change: 60 });
+        assert_eq!(machine.beverages_in_shortage(), &HashSet::new());
+        assert_eq!(
+            beverage_server_test_double.spied_requested_beverages(),
+            vec![(tea, SugarAmount::Zero)]
+        );
+        assert_eq!(inventory.stock_of(&Ingredient::TeaBags), 9)
+    }
+
+    #[test]
+    fn machine_asks_the_checker_again_after_a_shortage() {
+        let stub_beverage_quantity_checker = Shared::new(BeverageQuantityCheckerTestDouble::new());
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::clone(&stub_beverage_quantity_checker))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+        let coffee = Beverage::Coffee(HotBeverageOption::Standard);
+
+        stub_beverage_quantity_checker.stub_beverage_as_empty(coffee.clone());
+        machine.dispense(BeverageRequest::new(
+            &coffee,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+        stub_beverage_quantity_checker.stub_beverage_as_available(&coffee);
+        let outcome = machine.dispense(BeverageRequest::new(
+            &coffee,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert_eq!(outcome, DispenseOutcome::Served { change: 40 });
+        assert_eq!(machine.beverages_in_shortage(), &HashSet::new());
+        assert_eq!(
+            beverage_server_test_double.spied_requested_beverages(),
+            vec![(coffee, SugarAmount::Zero)]
+        )
+    }
+
+    #[test]
+    fn machine_keeps_the_shortage_when_the_restock_does_not_cover_it() {
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(IngredientInventory::default()))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        machine.dispense(BeverageRequest::new(
+            &Beverage::OrangeJuice,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+        machine.restock("bob", Ingredient::CoffeeGrams, 500);
+
+        assert_eq!(
+            machine.beverages_in_shortage(),
+            &HashSet::from([(Beverage::OrangeJuice, CupSize::Medium)])
+        )
+    }
+
+    #[test]
+    fn machine_prints_restock_report() {
+        let reports_printer_test_double = Shared::new(ReportsPrinterTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(IngredientInventory::default()))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::clone(&reports_printer_test_double))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        machine.restock("alice", Ingredient::TeaBags, 50);
+        machine.restock("bob", Ingredient::WaterMl, 5000);
+        machine.print_restock_report();
+
+        let printed_reports =
+            reports_printer_test_double.spied_restock_reports_requested_to_print();
+        let printed_restocks: Vec<(String, Ingredient, u32)> = printed_reports[0]
+            .restocks
+            .iter()
+            .map(|record| (record.operator.clone(), record.ingredient, record.quantity))
+            .collect();
+        assert_eq!(
+            printed_restocks,
+            vec![
+                (String::from("alice"), Ingredient::TeaBags, 50),
+                (String::from("bob"), Ingredient::WaterMl, 5000)
+            ]
+        );
+        assert_eq!(printed_reports[0].restocks, machine.restock_history())
+    }
+
+    #[test]
+    fn machine_prints_x_report_without_resetting_the_totals() {
+        let reports_printer_test_double = Shared::new(ReportsPrinterTestDouble::new());
+        let clock = Shared::new(ManualClock::default());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::clone(&reports_printer_test_double))
+            .set(Shared::new(DummyNotifier {}))
+            .with_clock(Shared::clone(&clock))
+            .build();
+        machine.dispense(BeverageRequest::new(
+            &Beverage::OrangeJuice,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        clock.advance(Duration::from_secs(60));
+        machine.print_purchases_report();
+        clock.advance(Duration::from_secs(60));
+        machine.print_purchases_report();
+
+        let printed_reports = reports_printer_test_double.spied_reports_requested_to_print();
+        assert_eq!(printed_reports[1].total_money_earned, 60);
+        assert_eq!(printed_reports[0].period_started_at, SystemTime::UNIX_EPOCH);
+        assert_eq!(printed_reports[1].period_started_at, SystemTime::UNIX_EPOCH);
+        assert_eq!(
+            printed_reports[0].period_ended_at,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(60)
+        );
+        assert_eq!(
+            printed_reports[1].period_ended_at,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(120)
+        );
+        assert_eq!(machine.closed_periods(), &[])
+    }
+
+    #[test]
+    fn machine_prints_z_report_closing_the_period() {
+        let reports_printer_test_double = Shared::new(ReportsPrinterTestDouble::new());
+        let clock = Shared::new(ManualClock::default());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::clone(&reports_printer_test_double))
+            .set(Shared::new(DummyNotifier {}))
+            .with_clock(Shared::clone(&clock))
+            .build();
+        machine.dispense(BeverageRequest::new(
+            &Beverage::OrangeJuice,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        clock.advance(Duration::from_secs(3600));
+        machine.print_z_report();
+        clock.advance(Duration::from_secs(60));
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+        machine.print_purchases_report();
+
+        let printed_reports = reports_printer_test_double.spied_reports_requested_to_print();
+        let z_report = &printed_reports[0];
+        let x_report = &printed_reports[1];
+        assert_eq!(
+            z_report.beverages_quantities,
+            HashMap::from([(Beverage::OrangeJuice, 1)])
+        );
+        assert_eq!(z_report.total_money_earned, 60);
+        assert_eq!(
+            x_report.beverages_quantities,
+            HashMap::from([(Beverage::Tea(HotBeverageOption::Standard), 1)])
+        );
+        assert_eq!(x_report.total_money_earned, 40);
+        assert_eq!(z_report.period_started_at, SystemTime::UNIX_EPOCH);
+        assert_eq!(
+            z_report.period_ended_at,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(3600)
+        );
+        assert_eq!(x_report.period_started_at, z_report.period_ended_at);
+        assert_eq!(
+            x_report.period_ended_at,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(3660)
+        );
+        assert_eq!(machine.closed_periods(), vec![z_report.clone()])
+    }
+
+    #[test]
+    fn machine_timestamps_dispensed_beverages() {
+        let clock = Shared::new(ManualClock::default());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_clock(Shared::clone(&clock))
+            .build();
+
+        machine.dispense(BeverageRequest::new(
+            &Beverage::OrangeJuice,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+        clock.advance(Duration::from_secs(30));
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert_eq!(
+            machine.dispensed_beverages(),
+            vec![
+                DispensedBeverage {
+                    beverage: Beverage::OrangeJuice,
+                    dispensed_at: SystemTime::UNIX_EPOCH,
+                },
+                DispensedBeverage {
+                    beverage: Beverage::Tea(HotBeverageOption::Standard),
+                    dispensed_at: SystemTime::UNIX_EPOCH + Duration::from_secs(30),
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn machine_records_every_attempted_transaction_in_the_sales_ledger() {
+        let clock = Shared::new(ManualClock::default());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_clock(Shared::clone(&clock))
+            .build();
+
+        machine.dispense(BeverageRequest::new(
+            &Beverage::OrangeJuice,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+        clock.advance(Duration::from_secs(30));
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Two,
+            30,
+        ));
+
+        assert_eq!(
+            machine.sales_ledger().entries(),
+            vec![
+                LedgerEntry {
+                    beverage: Beverage::OrangeJuice,
+                    sugar_amount: SugarAmount::Zero,
+                    cup_size: CupSize::Medium,
+                    add_ons: vec![],
+                    money_inserted: ENOUGH_MONEY,
+                    price_charged: 60,
+                    change: 40,
+                    outcome: TransactionOutcome::Served,
+                    occurred_at: SystemTime::UNIX_EPOCH,
+                },
+                LedgerEntry {
+                    beverage: Beverage::Tea(HotBeverageOption::Standard),
+                    sugar_amount: SugarAmount::Two,
+                    cup_size: CupSize::Medium,
+                    add_ons: vec![],
+                    money_inserted: 30,
+                    price_charged: 0,
+                    change: 0,
+                    outcome: TransactionOutcome::Underpaid,
+                    occurred_at: SystemTime::UNIX_EPOCH + Duration::from_secs(30),
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn machine_timestamps_refunds() {
+        let clock = Shared::new(ManualClock::default());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(FailingBeverageServerStub {
+                serving_failure: ServingFailure::Timeout,
+            }))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_clock(Shared::clone(&clock))
+            .build();
+
+        clock.advance(Duration::from_secs(45));
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Coffee(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert_eq!(
+            machine.refunds(),
+            vec![Refund {
+                amount: ENOUGH_MONEY,
+                refunded_at: SystemTime::UNIX_EPOCH + Duration::from_secs(45),
+            }]
+        );
+        assert_eq!(
+            machine.sales_ledger().entries()[0].outcome,
+            TransactionOutcome::ServingFailureRefund
+        );
+        assert_eq!(machine.dispensed_beverages(), vec![])
+    }
+
+    #[test]
+    fn machine_reports_hot_options_sugar_usage_and_failed_attempts() {
+        let reports_printer_test_double = Shared::new(ReportsPrinterTestDouble::new());
+        let stub_beverage_quantity_checker = Shared::new(BeverageQuantityCheckerTestDouble::new());
+        stub_beverage_quantity_checker
+            .stub_beverage_as_available(&Beverage::Coffee(HotBeverageOption::ExtraHot));
+        stub_beverage_quantity_checker
+            .stub_beverage_as_available(&Beverage::Tea(HotBeverageOption::Standard));
+        stub_beverage_quantity_checker.stub_beverage_as_empty(Beverage::OrangeJuice);
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(stub_beverage_quantity_checker)
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::clone(&reports_printer_test_double))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Coffee(HotBeverageOption::ExtraHot),
+            &SugarAmount::Two,
+            ENOUGH_MONEY,
+        ));
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::One,
+            ENOUGH_MONEY,
+        ));
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            10,
+        ));
+        machine.dispense(BeverageRequest::new(
+            &Beverage::OrangeJuice,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+        machine.print_purchases_report();
+
+        let report = &reports_printer_test_double.spied_reports_requested_to_print()[0];
+        assert_eq!(
+            report.beverages_revenues,
+            HashMap::from([
+                (Beverage::Coffee(HotBeverageOption::ExtraHot), 60),
+                (Beverage::Tea(HotBeverageOption::Standard), 40),
+            ])
+        );
+        assert_eq!(report.quantity_with_option(&HotBeverageOption::ExtraHot), 1);
+        assert_eq!(report.quantity_with_option(&HotBeverageOption::Standard), 1);
+        assert_eq!(report.sugar_doses_used, 3);
+        assert_eq!(report.stirring_sticks_used, 2);
+        assert_eq!(
+            report.underpaid_attempts,
+            HashMap::from([(Beverage::Tea(HotBeverageOption::Standard), 1)])
+        );
+        assert_eq!(
+            report.shortage_attempts,
+            HashMap::from([(Beverage::OrangeJuice, 1)])
+        )
+    }
+
+    #[test]
+    fn machine_restored_from_a_snapshot_keeps_sales_credit_and_stock() {
+        let inventory = Shared::new(
+            IngredientInventory::default()
+                .with_stock(Ingredient::TeaBags, 3)
+                .with_stock(Ingredient::WaterMl, 1000),
+        );
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::clone(&inventory))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+        machine.dispense(BeverageRequest::new(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+        machine.insert_coin(Coin::TwentyCents);
+
+        let reports_printer_test_double = Shared::new(ReportsPrinterTestDouble::new());
+        let restored_inventory = Shared::new(IngredientInventory::default());
+        let restored_machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::clone(&restored_inventory))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::clone(&reports_printer_test_double))
+            .set(Shared::new(DummyNotifier {}))
+            .with_restored_state(machine.snapshot())
+            .build();
+        restored_machine.print_purchases_report();
+
+        let printed_reports = reports_printer_test_double.spied_reports_requested_to_print();
+        assert_eq!(
+            printed_reports[0].beverages_quantities,
+            HashMap::from([(Beverage::Tea(HotBeverageOption::Standard), 1)])
+        );
+        assert_eq!(printed_reports[0].total_money_earned, 40);
+        assert_eq!(restored_machine.credit(), 20);
+        assert_eq!(restored_inventory.stock_of(&Ingredient::TeaBags), 2);
+        assert_eq!(restored_machine.coin_inventory(), machine.coin_inventory());
+        assert_eq!(restored_machine.snapshot(), machine.snapshot())
+    }
+
+    struct FailingEventStoreStub {}
+    impl EventStore for FailingEventStoreStub {
+        fn append(&self, _event: &MachineEvent) -> io::Result<()> {
+            Err(io::Error::from(io::ErrorKind::StorageFull))
+        }
+
+        fn events(&self) -> io::Result<Vec<MachineEvent>> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn machine_notifies_when_an_event_cannot_be_stored() {
+        let notifier_test_double = Shared::new(NotifierTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::clone(&notifier_test_double))
+            .with_clock(Shared::new(ManualClock::default()))
+            .with_event_store(Shared::new(FailingEventStoreStub {}))
+            .build();
+
+        machine.insert_coin(Coin::OneEuro);
+
+        assert_eq!(
+            notifier_test_double.spied_event_store_failures_notifications(),
+            vec![(
+                MachineEvent::CoinInserted {
+                    coin: Coin::OneEuro,
+                    occurred_at: SystemTime::UNIX_EPOCH,
+                },
+                io::ErrorKind::StorageFull
+            )]
+        );
+        assert_eq!(machine.credit(), 100)
+    }
+
+    #[test]
+    fn machine_rebuilt_from_its_event_log_has_the_same_cash_and_history() {
+        let event_store = Shared::new(InMemoryEventStore::default());
+        let clock = Shared::new(ManualClock::default());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(
+                IngredientInventory::default()
+                    .with_stock(Ingredient::CoffeeGrams, 7)
+                    .with_stock(Ingredient::WaterMl, 1000)
+                    .with_stock(Ingredient::SugarDoses, 10)
+                    .with_stock(Ingredient::StirringSticks, 10),
+            ))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_clock(Shared::clone(&clock))
+            .with_event_store(Shared::clone(&event_store))
+            .build();
+        let coffee = Beverage::Coffee(HotBeverageOption::Standard);
+
+        machine.dispense(BeverageRequest::new(&coffee, &SugarAmount::One, 200));
+        clock.advance(Duration::from_secs(60));
+        machine.insert_coin(Coin::OneEuro);
+        machine.select_beverage(&coffee, &SugarAmount::Zero);
+        machine.print_z_report();
+        clock.advance(Duration::from_secs(60));
+        machine.restock("alice", Ingredient::CoffeeGrams, 14);
+        machine.insert_coin(Coin::FiftyCents);
+        machine.insert_coin(Coin::TwentyCents);
+        machine.select_beverage(&coffee, &SugarAmount::Two);
+        machine.insert_coin(Coin::TenCents);
+        machine.cancel();
+        machine.insert_coin(Coin::FiveCents);
+
+        let replayed_machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(
+                IngredientInventory::default()
+                    .with_stock(Ingredient::CoffeeGrams, 7)
+                    .with_stock(Ingredient::WaterMl, 1000)
+                    .with_stock(Ingredient::SugarDoses, 10)
+                    .with_stock(Ingredient::StirringSticks, 10),
+            ))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_clock(Shared::new(ManualClock::default()))
+            .with_replayed_events(event_store.events().unwrap())
+            .build();
+
+        assert_eq!(replayed_machine.snapshot(), machine.snapshot());
+        assert_eq!(replayed_machine.refunds(), machine.refunds());
+        assert_eq!(replayed_machine.closed_periods(), machine.closed_periods());
+        assert_eq!(
+            replayed_machine.restock_history(),
+            machine.restock_history()
+        );
+        assert_eq!(
+            machine
+                .sales_ledger()
+                .entries()
+                .iter()
+                .map(|entry| entry.outcome.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                TransactionOutcome::Served,
+                TransactionOutcome::ShortageRefund,
+                TransactionOutcome::Served
+            ]
+        )
+    }
+
+    #[test]
+    fn machine_publishes_events_to_every_subscribed_listener() {
+        let stub_beverage_quantity_checker = Shared::new(BeverageQuantityCheckerTestDouble::new());
+        stub_beverage_quantity_checker.stub_beverage_as_empty(Beverage::OrangeJuice);
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(stub_beverage_quantity_checker)
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_clock(Shared::new(ManualClock::default()))
+            .build();
+        let telemetry_events = Shared::new(Mutex::new(Vec::new()));
+        let analytics_events = Shared::new(Mutex::new(Vec::new()));
+        machine.subscribe(Box::new(MachineEventListenerSpy {
+            received_events: Shared::clone(&telemetry_events),
+        }));
+        machine.subscribe(Box::new(MachineEventListenerSpy {
+            received_events: Shared::clone(&analytics_events),
+        }));
+
+        machine.insert_coin(Coin::OneEuro);
+        machine.select_beverage(&Beverage::OrangeJuice, &SugarAmount::Zero);
+
+        let received_events = telemetry_events.lock().unwrap().clone();
+        assert_eq!(
+            received_events[..3],
+            [
+                MachineEvent::CoinInserted {
+                    coin: Coin::OneEuro,
+                    occurred_at: SystemTime::UNIX_EPOCH,
+                },
+                MachineEvent::PaymentAccepted {
+                    beverage: Beverage::OrangeJuice,
+                    inserted_coins: vec![Coin::OneEuro],
+                    change: vec![Coin::TwentyCents, Coin::TwentyCents],
+                    paid_with_credit: true,
+                    occurred_at: SystemTime::UNIX_EPOCH,
+                },
+                MachineEvent::ShortageDetected {
+                    beverage: Beverage::OrangeJuice,
+                    cup_size: CupSize::Medium,
+                    occurred_at: SystemTime::UNIX_EPOCH,
+                },
+            ]
+        );
+        assert_eq!(received_events.len(), 5);
+        assert_eq!(*analytics_events.lock().unwrap(), received_events);
+    }
+
+    #[test]
+    fn machine_serves_beverages_added_to_its_catalog_at_their_catalog_price() {
+        let lemonade = Beverage::new("lemonade", None);
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_beverage_catalog(
+                BeverageCatalog::default()
+                    .with_definition(BeverageDefinition {
+                        id: String::from("lemonade"),
+                        display_name: String::from("lemonade"),
+                        drink_maker_code: String::from("L"),
+                        price: 70,
+                        temperature: Temperature::Cold,
+                        allowed_options: vec![],
+                        allowed_add_ons: vec![],
+                        max_sugar_doses: 0,
+                        recipe: Recipe::default().with(Ingredient::WaterMl, 250),
+                    })
+                    .unwrap(),
+            )
+            .build();
+
+        let outcome = machine.dispense(BeverageRequest::new(
+            &lemonade,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert_eq!(outcome, DispenseOutcome::Served { change: 30 });
+        assert_eq!(
+            beverage_server_test_double.spied_requested_beverages(),
+            vec![(lemonade, SugarAmount::Zero)]
+        )
+    }
+
+    #[test_case(Beverage::new("espresso", Some(HotBeverageOption::Standard)) ; "beverage missing from the catalog")]
+    #[test_case(Beverage::new("lemonade", None) ; "cold beverage missing from the catalog")]
+    fn machine_rejects_beverages_it_cannot_serve(beverage: Beverage) {
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+        machine.insert_coin(Coin::OneEuro);
+
+        let outcome = machine.select_beverage(&beverage, &SugarAmount::Zero);
+
+        assert_eq!(
+            outcome,
+            DispenseOutcome::Failed(DispenseError::InvalidRequest(
+                InvalidBeverageRequest::UnknownBeverage
+            ))
+        );
+        assert_eq!(
+            beverage_server_test_double.spied_requested_beverages(),
+            vec![]
+        );
+        assert_eq!(machine.credit(), 100);
+        assert_eq!(
+            machine.sales_ledger().entries()[0].outcome,
+            TransactionOutcome::Rejected
+        )
+    }
+
+    #[test_case(CupSize::Small, 50 ; "small coffee")]
+    #[test_case(CupSize::Medium, 40 ; "medium coffee")]
+    #[test_case(CupSize::Large, 20 ; "large coffee")]
+    fn machine_charges_and_serves_the_requested_cup_size(cup_size: CupSize, expected_change: u32) {
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let outcome = machine.dispense(
+            BeverageRequest::new(
+                &Beverage::Coffee(HotBeverageOption::Standard),
+                &SugarAmount::Zero,
+                ENOUGH_MONEY,
+            )
+            .with_cup_size(cup_size),
+        );
+
+        assert_eq!(
+            outcome,
+            DispenseOutcome::Served {
+                change: expected_change
+            }
+        );
+        assert_eq!(
+            beverage_server_test_double.spied_requested_cup_sizes(),
+            vec![cup_size]
+        );
+        assert_eq!(machine.sales_ledger().entries()[0].cup_size, cup_size)
+    }
+
+    #[test]
+    fn machine_charges_the_credit_for_the_selected_cup_size() {
+        let reports_printer_test_double = Shared::new(ReportsPrinterTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::clone(&reports_printer_test_double))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+        machine.insert_coin(Coin::FiftyCents);
+        machine.insert_coin(Coin::TenCents);
+
+        let outcome = machine.select_sized_beverage(
+            &Beverage::Tea(HotBeverageOption::Standard),
+            &SugarAmount::Zero,
+            &CupSize::Large,
+        );
+        machine.print_purchases_report();
+
+        assert_eq!(outcome, DispenseOutcome::Served { change: 0 });
+        let printed_reports = reports_printer_test_double.spied_reports_requested_to_print();
+        assert_eq!(printed_reports[0].quantity_of_size(&CupSize::Large), 1);
+        assert_eq!(printed_reports[0].total_money_earned, 60)
+    }
+
+    #[test]
+    fn machine_serves_a_medium_cup_after_a_large_cup_shortage() {
+        let inventory = Shared::new(
+            IngredientInventory::default()
+                .with_stock(Ingredient::CoffeeGrams, 10)
+                .with_stock(Ingredient::WaterMl, 1000),
+        );
+        let coffee = Beverage::Coffee(HotBeverageOption::Standard);
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::clone(&inventory))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let large_outcome = machine.dispense(
+            BeverageRequest::new(&coffee, &SugarAmount::Zero, ENOUGH_MONEY)
+                .with_cup_size(CupSize::Large),
+        );
+        let medium_outcome = machine.dispense(
+            BeverageRequest::new(&coffee, &SugarAmount::Zero, ENOUGH_MONEY)
+                .with_cup_size(CupSize::Medium),
+        );
+
+        assert!(matches!(
+            large_outcome,
+            DispenseOutcome::Failed(DispenseError::Shortage { .. })
+        ));
+        assert_eq!(medium_outcome, DispenseOutcome::Served { change: 40 });
+        assert_eq!(
+            machine.beverages_in_shortage(),
+            &HashSet::from([(coffee, CupSize::Large)])
+        );
+        assert_eq!(inventory.stock_of(&Ingredient::CoffeeGrams), 3)
+    }
+
+    fn stocked_inventory_without(missing_ingredient: Ingredient) -> IngredientInventory {
+        Ingredient::ALL
+            .into_iter()
+            .fold(IngredientInventory::default(), |inventory, ingredient| {
+                inventory.with_stock(ingredient, 1000)
+            })
+            .with_stock(missing_ingredient, 0)
+    }
+
+    #[test_case(SugarAmount::One, Ingredient::SugarDoses ; "sugar")]
+    #[test_case(SugarAmount::stick_only(), Ingredient::StirringSticks ; "stirring stick")]
+    #[test_case(SugarAmount::One.with_sweetener(Sweetener::SugarFree), Ingredient::SweetenerDoses ; "sweetener")]
+    fn machine_reports_a_missing_sweetening_ingredient_instead_of_an_empty_beverage(
+        sugar_amount: SugarAmount,
+        missing_ingredient: Ingredient,
+    ) {
+        let coffee = Beverage::Coffee(HotBeverageOption::Standard);
+        let display_test_double = Shared::new(DisplayTestDouble::new());
+        let notifier_test_double = Shared::new(NotifierTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(stocked_inventory_without(missing_ingredient)))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::clone(&notifier_test_double))
+            .with_clock(Shared::new(ManualClock::default()))
+            .build();
+
+        let sweetened_outcome =
+            machine.dispense(BeverageRequest::new(&coffee, &sugar_amount, ENOUGH_MONEY));
+        let plain_outcome = machine.dispense(BeverageRequest::new(
+            &coffee,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert!(matches!(
+            sweetened_outcome,
+            DispenseOutcome::Failed(DispenseError::Shortage { .. })
+        ));
+        assert_eq!(plain_outcome, DispenseOutcome::Served { change: 40 });
+        assert_eq!(
+            notifier_test_double.spied_missing_ingredients_notifications(),
+            vec![(missing_ingredient, SystemTime::UNIX_EPOCH)]
+        );
+        assert_eq!(
+            notifier_test_double.spied_missing_beverages_notifications(),
+            vec![]
+        );
+        assert_eq!(
+            display_test_double.spied_missing_ingredient_message_requests(),
+            vec![missing_ingredient]
+        );
+        assert_eq!(
+            display_test_double.spied_beverage_shortage_message_requests(),
+            vec![]
+        )
+    }
+
+    #[test_case(AddOn::WhippedCream, Ingredient::WhippedCreamGrams ; "whipped cream")]
+    #[test_case(AddOn::Milk, Ingredient::MilkMl ; "milk")]
+    fn machine_serves_a_plain_beverage_after_a_shortage_of_one_of_its_add_ons(
+        add_on: AddOn,
+        missing_ingredient: Ingredient,
+    ) {
+        let display_test_double = Shared::new(DisplayTestDouble::new());
+        let notifier_test_double = Shared::new(NotifierTestDouble::new());
+        let inventory = Shared::new(
+            IngredientInventory::default()
+                .with_stock(Ingredient::ChocolatePowderGrams, 100)
+                .with_stock(Ingredient::WaterMl, 1000),
+        );
+        let hot_chocolate = Beverage::HotChocolate(HotBeverageOption::Standard);
+        let mut machine = MachineBuilder::default()
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::clone(&inventory))
+            .set(Shared::clone(&display_test_double))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::clone(&notifier_test_double))
+            .with_clock(Shared::new(ManualClock::default()))
+            .build();
+
+        let add_on_outcome = machine.dispense(
+            BeverageRequest::new(&hot_chocolate, &SugarAmount::Zero, ENOUGH_MONEY)
+                .with_add_ons(&[add_on]),
+        );
+        let plain_outcome = machine.dispense(BeverageRequest::new(
+            &hot_chocolate,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert!(matches!(
+            add_on_outcome,
+            DispenseOutcome::Failed(DispenseError::Shortage { .. })
+        ));
+        assert_eq!(plain_outcome, DispenseOutcome::Served { change: 50 });
+        assert_eq!(machine.beverages_in_shortage(), &HashSet::new());
+        assert_eq!(inventory.stock_of(&Ingredient::ChocolatePowderGrams), 75);
+        assert_eq!(
+            notifier_test_double.spied_missing_ingredients_notifications(),
+            vec![(missing_ingredient, SystemTime::UNIX_EPOCH)]
+        );
+        assert_eq!(
+            notifier_test_double.spied_missing_beverages_notifications(),
+            vec![]
+        );
+        assert_eq!(
+            display_test_double.spied_missing_ingredient_message_requests(),
+            vec![missing_ingredient]
+        );
+        assert_eq!(
+            display_test_double.spied_beverage_shortage_message_requests(),
+            vec![]
+        )
+    }
+
+    #[test]
+    fn machine_charges_each_add_on_and_serves_it_with_the_beverage() {
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let outcome = machine.dispense(
+            BeverageRequest::new(
+                &Beverage::Coffee(HotBeverageOption::Standard),
+                &SugarAmount::Zero,
+                ENOUGH_MONEY,
+            )
+            .with_add_ons(&[AddOn::Milk, AddOn::ExtraShot]),
+        );
+
+        assert_eq!(outcome, DispenseOutcome::Served { change: 0 });
+        assert_eq!(
+            beverage_server_test_double.spied_requested_add_ons(),
+            vec![vec![AddOn::Milk, AddOn::ExtraShot]]
+        );
+        assert_eq!(
+            machine.sales_ledger().entries()[0].add_ons,
+            vec![AddOn::Milk, AddOn::ExtraShot]
+        )
+    }
+
+    #[test]
+    fn machine_rejects_add_ons_the_beverage_does_not_allow() {
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let outcome = machine.dispense(
+            BeverageRequest::new(&Beverage::OrangeJuice, &SugarAmount::Zero, ENOUGH_MONEY)
+                .with_add_ons(&[AddOn::Milk]),
+        );
+
+        let invalid_request = InvalidBeverageRequest::AddOnNotAllowed(AddOn::Milk);
+        assert_eq!(
+            invalid_request.to_string(),
+            "milk cannot be added to this beverage"
+        );
+        assert_eq!(
+            outcome,
+            DispenseOutcome::Failed(DispenseError::InvalidRequest(invalid_request))
+        );
+        assert_eq!(
+            beverage_server_test_double.spied_requested_add_ons(),
+            Vec::<Vec<AddOn>>::new()
+        );
+        assert_eq!(
+            machine.sales_ledger().entries()[0].outcome,
+            TransactionOutcome::Rejected
+        )
+    }
+
+    #[test_case(Beverage::OrangeJuice, SugarAmount::One, InvalidBeverageRequest::SugarNotAllowed ; "sugar in an orange juice")]
+    #[test_case(Beverage::OrangeJuice, SugarAmount::One.with_sweetener(Sweetener::SugarFree), InvalidBeverageRequest::SugarNotAllowed ; "sweetener in an orange juice")]
+    #[test_case(Beverage::Coffee(HotBeverageOption::Standard), SugarAmount::doses(3), InvalidBeverageRequest::TooMuchSugar { max_doses: 2 } ; "three sugars in a coffee")]
+    fn machine_rejects_sugar_beyond_the_beverage_maximum(
+        beverage: Beverage,
+        sugar_amount: SugarAmount,
+        invalid_request: InvalidBeverageRequest,
+    ) {
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let outcome =
+            machine.dispense(BeverageRequest::new(&beverage, &sugar_amount, ENOUGH_MONEY));
+
+        assert_eq!(
+            outcome,
+            DispenseOutcome::Failed(DispenseError::InvalidRequest(invalid_request))
+        );
+        assert!(beverage_server_test_double
+            .spied_requested_beverages()
+            .is_empty())
+    }
+
+    #[test_case(Beverage::cold("tea", ColdBeverageOption::WithIce), Temperature::Hot ; "tea with ice")]
+    #[test_case(Beverage::cold("coffee", ColdBeverageOption::Chilled), Temperature::Hot ; "chilled coffee")]
+    #[test_case(Beverage::new("orange_juice", Some(HotBeverageOption::ExtraHot)), Temperature::Cold ; "extra hot orange juice")]
+    #[test_case(Beverage::new("orange_juice", Some(HotBeverageOption::Standard)), Temperature::Cold ; "orange juice with a hot option")]
+    fn machine_rejects_options_of_the_other_temperature(beverage: Beverage, served: Temperature) {
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .build();
+
+        let outcome = machine.dispense(BeverageRequest::new(
+            &beverage,
+            &SugarAmount::Zero,
+            ENOUGH_MONEY,
+        ));
+
+        assert_eq!(
+            outcome,
+            DispenseOutcome::Failed(DispenseError::InvalidRequest(
+                InvalidBeverageRequest::TemperatureMismatch { served }
+            ))
+        );
+        assert!(beverage_server_test_double
+            .spied_requested_beverages()
+            .is_empty())
+    }
+
+    #[test_case(Temperature::Hot, "this beverage is only served hot" ; "hot")]
+    #[test_case(Temperature::Cold, "this beverage is only served cold" ; "cold")]
+    fn temperature_mismatches_are_described_by_the_served_temperature(
+        served: Temperature,
+        expected_description: &str,
+    ) {
+        let invalid_request = InvalidBeverageRequest::TemperatureMismatch { served };
+
+        assert_eq!(invalid_request.to_string(), expected_description)
+    }
+
+    #[test_case(SugarAmount::doses(3) ; "three sugars")]
+    #[test_case(SugarAmount::stick_only() ; "a stick without sugar")]
+    fn machine_serves_sugar_within_the_configured_maximum(sugar_amount: SugarAmount) {
+        let coffee = Beverage::Coffee(HotBeverageOption::Standard);
+        let beverage_server_test_double = Shared::new(BeverageServerTestDouble::new());
+        let mut machine = MachineBuilder::default()
+            .set(Shared::clone(&beverage_server_test_double))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
+            .with_beverage_catalog(BeverageCatalog::default().with_max_sugar_doses("coffee", 3))
+            .build();
+
+        let outcome = machine.dispense(BeverageRequest::new(&coffee, &sugar_amount, ENOUGH_MONEY));
+
+        assert_eq!(outcome, DispenseOutcome::Served { change: 40 });
+        assert_eq!(
+            beverage_server_test_double.spied_requested_beverages(),
+            vec![(coffee, sugar_amount)]
+        )
+    }
 }
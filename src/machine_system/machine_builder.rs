@@ -1,16 +1,29 @@
-use std::rc::Rc;
+use std::collections::HashSet;
 
 use super::{
     beverages::{
-        beverage_quantity_checker::BeverageQuantityChecker, beverage_server::BeverageServer,
+        async_beverage_server::{AsyncBeverageServer, SyncBeverageServerAdapter},
+        beverage_catalog::BeverageCatalog,
+        beverage_quantity_checker::BeverageQuantityChecker,
+        beverage_server::BeverageServer,
         dispenser::Dispenser,
     },
     cashier::Cashier,
+    clock::{Clock, SystemClock},
+    coins::CoinInventory,
     display::Display,
+    event_store::{EventStore, InMemoryEventStore},
+    low_stock::{LowStockMonitor, LowStockThresholds},
     machine::Machine,
+    machine_event::MachineEvent,
+    machine_state::MachineState,
     notifier::Notifier,
+    price_list::PriceList,
     reports_printer::ReportsPrinter,
+    restock::RestockHistory,
+    sales_ledger::SalesLedger,
 };
+use crate::shared::Shared;
 
 #[derive(Default)]
 pub struct MachineBuilder {}
@@ -18,38 +31,38 @@ pub struct MachineBuilder {}
 impl MachineBuilder {
     pub fn set(
         self,
-        beverage_server: Rc<impl BeverageServer + 'static>,
+        beverage_server: Shared<impl BeverageServer + 'static>,
     ) -> RequiresBeverageQuantityChecker {
         RequiresBeverageQuantityChecker::new(beverage_server)
     }
 }
 
 pub struct RequiresBeverageQuantityChecker {
-    beverage_server: Rc<dyn BeverageServer>,
+    beverage_server: Shared<dyn BeverageServer>,
 }
 
 impl RequiresBeverageQuantityChecker {
-    fn new(beverage_server: Rc<impl BeverageServer + 'static>) -> Self {
+    fn new(beverage_server: Shared<impl BeverageServer + 'static>) -> Self {
         Self { beverage_server }
     }
 
     pub fn set(
         self,
-        beverage_quantity_checker: Rc<impl BeverageQuantityChecker + 'static>,
+        beverage_quantity_checker: Shared<impl BeverageQuantityChecker + 'static>,
     ) -> RequiresDisplay {
         RequiresDisplay::new(self, beverage_quantity_checker)
     }
 }
 
 pub struct RequiresDisplay {
-    beverage_server: Rc<dyn BeverageServer>,
-    beverage_quantity_checker: Rc<dyn BeverageQuantityChecker>,
+    beverage_server: Shared<dyn BeverageServer>,
+    beverage_quantity_checker: Shared<dyn BeverageQuantityChecker>,
 }
 
 impl RequiresDisplay {
     fn new(
         requires_beverage_quantity_checker: RequiresBeverageQuantityChecker,
-        beverage_quantity_checker: Rc<impl BeverageQuantityChecker + 'static>,
+        beverage_quantity_checker: Shared<impl BeverageQuantityChecker + 'static>,
     ) -> Self {
         Self {
             beverage_server: requires_beverage_quantity_checker.beverage_server,
@@ -57,19 +70,19 @@ impl RequiresDisplay {
         }
     }
 
-    pub fn set(self, display: Rc<impl Display + 'static>) -> RequiresReportsPrinter {
+    pub fn set(self, display: Shared<impl Display + 'static>) -> RequiresReportsPrinter {
         RequiresReportsPrinter::new(self, display)
     }
 }
 
 pub struct RequiresReportsPrinter {
-    beverage_server: Rc<dyn BeverageServer>,
-    beverage_quantity_checker: Rc<dyn BeverageQuantityChecker>,
-    display: Rc<dyn Display>,
+    beverage_server: Shared<dyn BeverageServer>,
+    beverage_quantity_checker: Shared<dyn BeverageQuantityChecker>,
+    display: Shared<dyn Display>,
 }
 
 impl RequiresReportsPrinter {
-    fn new(requires_display: RequiresDisplay, display: Rc<impl Display + 'static>) -> Self {
+    fn new(requires_display: RequiresDisplay, display: Shared<impl Display + 'static>) -> Self {
         Self {
             beverage_server: requires_display.beverage_server,
             beverage_quantity_checker: requires_display.beverage_quantity_checker,
@@ -77,22 +90,22 @@ impl RequiresReportsPrinter {
         }
     }
 
-    pub fn set(self, report_printer: Rc<impl ReportsPrinter + 'static>) -> RequiresNotifier {
+    pub fn set(self, report_printer: Shared<impl ReportsPrinter + 'static>) -> RequiresNotifier {
         RequiresNotifier::new(self, report_printer)
     }
 }
 
 pub struct RequiresNotifier {
-    beverage_server: Rc<dyn BeverageServer>,
-    beverage_quantity_checker: Rc<dyn BeverageQuantityChecker>,
-    display: Rc<dyn Display>,
-    reports_printer: Rc<dyn ReportsPrinter>,
+    beverage_server: Shared<dyn BeverageServer>,
+    beverage_quantity_checker: Shared<dyn BeverageQuantityChecker>,
+    display: Shared<dyn Display>,
+    reports_printer: Shared<dyn ReportsPrinter>,
 }
 
 impl RequiresNotifier {
     fn new(
         requires_reports_printer: RequiresReportsPrinter,
-        reports_printer: Rc<impl ReportsPrinter + 'static>,
+        reports_printer: Shared<impl ReportsPrinter + 'static>,
     ) -> Self {
         Self {
             beverage_server: requires_reports_printer.beverage_server,
@@ -102,60 +115,164 @@ impl RequiresNotifier {
         }
     }
 
-    pub fn set(self, notifier: Rc<impl Notifier + 'static>) -> MachineBuilderReadyForBuilding {
+    pub fn set(self, notifier: Shared<impl Notifier + 'static>) -> MachineBuilderReadyForBuilding {
         MachineBuilderReadyForBuilding::new(self, notifier)
     }
 }
 
 pub struct MachineBuilderReadyForBuilding {
-    beverage_server: Rc<dyn BeverageServer>,
-    beverage_quantity_checker: Rc<dyn BeverageQuantityChecker>,
-    display: Rc<dyn Display>,
-    reports_printer: Rc<dyn ReportsPrinter>,
-    notifier: Rc<dyn Notifier>,
+    beverage_server: Shared<dyn BeverageServer>,
+    beverage_quantity_checker: Shared<dyn BeverageQuantityChecker>,
+    display: Shared<dyn Display>,
+    reports_printer: Shared<dyn ReportsPrinter>,
+    notifier: Shared<dyn Notifier>,
+    beverage_catalog: BeverageCatalog,
+    price_list: Option<PriceList>,
+    coin_inventory: CoinInventory,
+    low_stock_thresholds: LowStockThresholds,
+    async_beverage_server: Option<Shared<dyn AsyncBeverageServer>>,
+    clock: Shared<dyn Clock>,
+    restored_state: Option<MachineState>,
+    event_store: Shared<dyn EventStore>,
+    replayed_events: Vec<MachineEvent>,
 }
 
 impl MachineBuilderReadyForBuilding {
-    fn new(requires_notifier: RequiresNotifier, notifier: Rc<impl Notifier + 'static>) -> Self {
+    fn new(requires_notifier: RequiresNotifier, notifier: Shared<impl Notifier + 'static>) -> Self {
         Self {
             beverage_server: requires_notifier.beverage_server,
             beverage_quantity_checker: requires_notifier.beverage_quantity_checker,
             display: requires_notifier.display,
             reports_printer: requires_notifier.reports_printer,
             notifier,
+            beverage_catalog: BeverageCatalog::default(),
+            price_list: None,
+            coin_inventory: CoinInventory::standard_float(),
+            low_stock_thresholds: LowStockThresholds::default(),
+            async_beverage_server: None,
+            clock: Shared::new(SystemClock::default()),
+            restored_state: None,
+            event_store: Shared::new(InMemoryEventStore::default()),
+            replayed_events: Vec::new(),
         }
     }
 
+    pub fn with_beverage_catalog(mut self, beverage_catalog: BeverageCatalog) -> Self {
+        self.beverage_catalog = beverage_catalog;
+        self
+    }
+
+    pub fn with_price_list(mut self, price_list: PriceList) -> Self {
+        self.price_list = Some(price_list);
+        self
+    }
+
+    pub fn with_coin_inventory(mut self, coin_inventory: CoinInventory) -> Self {
+        self.coin_inventory = coin_inventory;
+        self
+    }
+
+    pub fn with_low_stock_thresholds(mut self, low_stock_thresholds: LowStockThresholds) -> Self {
+        self.low_stock_thresholds = low_stock_thresholds;
+        self
+    }
+
+    pub fn with_async_beverage_server(
+        mut self,
+        async_beverage_server: Shared<impl AsyncBeverageServer + 'static>,
+    ) -> Self {
+        self.async_beverage_server = Some(async_beverage_server);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Shared<impl Clock + 'static>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_restored_state(mut self, machine_state: MachineState) -> Self {
+        self.restored_state = Some(machine_state);
+        self
+    }
+
+    pub fn with_event_store(mut self, event_store: Shared<impl EventStore + 'static>) -> Self {
+        self.event_store = event_store;
+        self
+    }
+
+    pub fn with_replayed_events(mut self, events: Vec<MachineEvent>) -> Self {
+        self.replayed_events = events;
+        self
+    }
+
     pub fn build(self) -> Machine {
-        Machine {
-            dispenser: Dispenser::new(self.beverage_server, self.beverage_quantity_checker),
-            cashier: Cashier::new(),
+        self.beverage_server
+            .use_beverage_catalog(&self.beverage_catalog);
+        self.beverage_quantity_checker
+            .use_beverage_catalog(&self.beverage_catalog);
+        self.display.use_beverage_catalog(&self.beverage_catalog);
+        if let Some(async_beverage_server) = &self.async_beverage_server {
+            async_beverage_server.use_beverage_catalog(&self.beverage_catalog);
+        }
+        let async_beverage_server = self.async_beverage_server.unwrap_or_else(|| {
+            Shared::new(SyncBeverageServerAdapter::new(Shared::clone(
+                &self.beverage_server,
+            )))
+        });
+        let price_list = match self.price_list {
+            Some(price_list) => self.beverage_catalog.price_list().overridden_by(price_list),
+            None => self.beverage_catalog.price_list(),
+        };
+        let mut machine = Machine {
+            dispenser: Dispenser::new(
+                self.beverage_server,
+                async_beverage_server,
+                self.beverage_quantity_checker,
+            ),
+            cashier: Cashier::new(price_list, self.coin_inventory),
             display: self.display,
             reports_printer: self.reports_printer,
             notifier: self.notifier,
+            low_stock_monitor: LowStockMonitor::new(self.low_stock_thresholds),
+            beverages_in_shortage: HashSet::new(),
+            restock_history: RestockHistory::default(),
+            sales_ledger: SalesLedger::default(),
+            period_first_entry: 0,
+            period_started_at: self.clock.now(),
+            closed_periods: Vec::new(),
+            clock: self.clock,
+            event_store: self.event_store,
+            listeners: Vec::new(),
+            beverage_catalog: self.beverage_catalog,
+        };
+        if let Some(machine_state) = self.restored_state {
+            machine.restore(machine_state);
         }
+        for event in &self.replayed_events {
+            machine.replay(event);
+        }
+        machine
     }
 }
 
 #[cfg(test)]
 mod machine_builder_tests {
-    use std::rc::Rc;
-
     use crate::machine_system::machine::machine_tests::{
         DummyBeverageServer, DummyDisplay, DummyNotifier, DummyReportsPrinter,
         InfiniteBeverageQuantityCheckerFake,
     };
 
     use super::MachineBuilder;
+    use crate::shared::Shared;
 
     #[test]
     fn build_a_machine() {
         MachineBuilder::default()
-            .set(Rc::new(DummyBeverageServer {}))
-            .set(Rc::new(InfiniteBeverageQuantityCheckerFake {}))
-            .set(Rc::new(DummyDisplay {}))
-            .set(Rc::new(DummyReportsPrinter {}))
-            .set(Rc::new(DummyNotifier {}))
+            .set(Shared::new(DummyBeverageServer {}))
+            .set(Shared::new(InfiniteBeverageQuantityCheckerFake {}))
+            .set(Shared::new(DummyDisplay {}))
+            .set(Shared::new(DummyReportsPrinter {}))
+            .set(Shared::new(DummyNotifier {}))
             .build();
     }
 }
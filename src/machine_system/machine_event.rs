@@ -0,0 +1,355 @@
+use std::{str::FromStr, time::SystemTime};
+
+use super::{
+    beverages::{add_on::AddOn, beverage::Beverage, cup_size::CupSize, sugar_amount::SugarAmount},
+    coins::Coin,
+    record_format::{
+        beverage_name, coin_name, coins_field, cup_size_name, escape_text, ingredient_name,
+        outcome_name, parse_beverage, parse_coin, parse_coins_field, parse_cup_size,
+        parse_ingredient, parse_number, parse_outcome, parse_serving_fields, parse_sugar_amount,
+        parse_timestamp, serving_fields, sugar_amount_name, timestamp, unescape_text, InvalidValue,
+    },
+    restock::RestockRecord,
+    sales_ledger::LedgerEntry,
+};
+
+const PAID_WITH_CREDIT: &str = "credit";
+const PAID_WITH_CASH: &str = "cash";
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum MachineEvent {
+    CoinInserted {
+        coin: Coin,
+        occurred_at: SystemTime,
+    },
+    CreditCancelled {
+        coins: Vec<Coin>,
+        occurred_at: SystemTime,
+    },
+    PaymentAccepted {
+        beverage: Beverage,
+        inserted_coins: Vec<Coin>,
+        change: Vec<Coin>,
+        paid_with_credit: bool,
+        occurred_at: SystemTime,
+    },
+    BeverageServed {
+        beverage: Beverage,
+        sugar_amount: SugarAmount,
+        cup_size: CupSize,
+        add_ons: Vec<AddOn>,
+        occurred_at: SystemTime,
+    },
+    ShortageDetected {
+        beverage: Beverage,
+        cup_size: CupSize,
+        occurred_at: SystemTime,
+    },
+    PaymentRefunded {
+        inserted_coins: Vec<Coin>,
+        change: Vec<Coin>,
+        occurred_at: SystemTime,
+    },
+    TransactionRecorded(LedgerEntry),
+    PurchasesReportPrinted {
+        occurred_at: SystemTime,
+    },
+    ZReportPrinted {
+        occurred_at: SystemTime,
+    },
+    Restocked(RestockRecord),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ParseMachineEventError {
+    UnknownEvent(String),
+    InvalidValue(String),
+}
+
+impl MachineEvent {
+    pub fn occurred_at(&self) -> SystemTime {
+        match self {
+            MachineEvent::CoinInserted { occurred_at, .. }
+            | MachineEvent::CreditCancelled { occurred_at, .. }
+            | MachineEvent::PaymentAccepted { occurred_at, .. }
+            | MachineEvent::BeverageServed { occurred_at, .. }
+            | MachineEvent::ShortageDetected { occurred_at, .. }
+            | MachineEvent::PaymentRefunded { occurred_at, .. }
+            | MachineEvent::PurchasesReportPrinted { occurred_at }
+            | MachineEvent::ZReportPrinted { occurred_at } => *occurred_at,
+            MachineEvent::TransactionRecorded(entry) => entry.occurred_at,
+            MachineEvent::Restocked(restock) => restock.restocked_at,
+        }
+    }
+}
+
+impl std::fmt::Display for MachineEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ", timestamp(self.occurred_at()))?;
+        match self {
+            MachineEvent::CoinInserted { coin, .. } => {
+                write!(f, "coin_inserted {}", coin_name(coin))
+            }
+            MachineEvent::CreditCancelled { coins, .. } => {
+                write!(f, "credit_cancelled {}", coins_field(coins))
+            }
+            MachineEvent::PaymentAccepted {
+                beverage,
+                inserted_coins,
+                change,
+                paid_with_credit,
+                ..
+            } => write!(
+                f,
+                "payment_accepted {} {} {} {}",
+                beverage_name(beverage),
+                coins_field(inserted_coins),
+                coins_field(change),
+                if *paid_with_credit {
+                    PAID_WITH_CREDIT
+                } else {
+                    PAID_WITH_CASH
+                }
+            ),
+            MachineEvent::BeverageServed {
+                beverage,
+                sugar_amount,
+                cup_size,
+                add_ons,
+                ..
+            } => write!(
+                f,
+                "beverage_served {} {} {}",
+                beverage_name(beverage),
+                sugar_amount_name(sugar_amount),
+                serving_fields(cup_size, add_ons)
+            ),
+            MachineEvent::ShortageDetected {
+                beverage, cup_size, ..
+            } => write!(
+                f,
+                "shortage_detected {} {}",
+                beverage_name(beverage),
+                cup_size_name(cup_size)
+            ),
+            MachineEvent::PaymentRefunded {
+                inserted_coins,
+                change,
+                ..
+            } => write!(
+                f,
+                "payment_refunded {} {}",
+                coins_field(inserted_coins),
+                coins_field(change)
+            ),
+            MachineEvent::TransactionRecorded(entry) => write!(
+                f,
+                "transaction_recorded {} {} {} {} {} {} {}",
+                beverage_name(&entry.beverage),
+                sugar_amount_name(&entry.sugar_amount),
+                entry.money_inserted,
+                entry.price_charged,
+                entry.change,
+                outcome_name(&entry.outcome),
+                serving_fields(&entry.cup_size, &entry.add_ons)
+            ),
+            MachineEvent::PurchasesReportPrinted { .. } => write!(f, "purchases_report_printed"),
+            MachineEvent::ZReportPrinted { .. } => write!(f, "z_report_printed"),
+            MachineEvent::Restocked(restock) => write!(
+                f,
+                "restocked {} {} {}",
+                ingredient_name(&restock.ingredient),
+                restock.quantity,
+                escape_text(&restock.operator)
+            ),
+        }
+    }
+}
+
+impl FromStr for MachineEvent {
+    type Err = ParseMachineEventError;
+
+    fn from_str(event: &str) -> Result<Self, Self::Err> {
+        let unknown_event = || ParseMachineEventError::UnknownEvent(String::from(event));
+        let (occurred_at, event_fields) = event.split_once(' ').ok_or_else(unknown_event)?;
+        let occurred_at = parse_timestamp(occurred_at)?;
+
+        if let Some(restock) = event_fields.strip_prefix("restocked ") {
+            let mut restock_fields = restock.splitn(3, ' ');
+            let (Some(ingredient), Some(quantity), Some(operator)) = (
+                restock_fields.next(),
+                restock_fields.next(),
+                restock_fields.next(),
+            ) else {
+                return Err(unknown_event());
+            };
+            return Ok(MachineEvent::Restocked(RestockRecord {
+                operator: unescape_text(operator)?,
+                ingredient: parse_ingredient(ingredient)?,
+                quantity: parse_number(quantity)?,
+                restocked_at: occurred_at,
+            }));
+        }
+
+        let fields: Vec<&str> = event_fields.split(' ').collect();
+        let machine_event = match fields[..] {
+            ["coin_inserted", coin] => MachineEvent::CoinInserted {
+                coin: parse_coin(coin)?,
+                occurred_at,
+            },
+            ["credit_cancelled", coins] => MachineEvent::CreditCancelled {
+                coins: parse_coins_field(coins)?,
+                occurred_at,
+            },
+            ["payment_accepted", beverage, inserted_coins, change, paid_with] => {
+                MachineEvent::PaymentAccepted {
+                    beverage: parse_beverage(beverage)?,
+                    inserted_coins: parse_coins_field(inserted_coins)?,
+                    change: parse_coins_field(change)?,
+                    paid_with_credit: match paid_with {
+                        PAID_WITH_CREDIT => true,
+                        PAID_WITH_CASH => false,
+                        other => {
+                            return Err(ParseMachineEventError::InvalidValue(String::from(other)))
+                        }
+                    },
+                    occurred_at,
+                }
+            }
+            ["beverage_served", beverage, sugar_amount, ref serving @ ..] => {
+                let (cup_size, add_ons) = parse_serving_fields(serving)?;
+                MachineEvent::BeverageServed {
+                    beverage: parse_beverage(beverage)?,
+                    sugar_amount: parse_sugar_amount(sugar_amount)?,
+                    cup_size,
+                    add_ons,
+                    occurred_at,
+                }
+            }
+            ["shortage_detected", beverage] => MachineEvent::ShortageDetected {
+                beverage: parse_beverage(beverage)?,
+                cup_size: CupSize::default(),
+                occurred_at,
+            },
+            ["shortage_detected", beverage, cup_size] => MachineEvent::ShortageDetected {
+                beverage: parse_beverage(beverage)?,
+                cup_size: parse_cup_size(cup_size)?,
+                occurred_at,
+            },
+            ["payment_refunded", inserted_coins, change] => MachineEvent::PaymentRefunded {
+                inserted_coins: parse_coins_field(inserted_coins)?,
+                change: parse_coins_field(change)?,
+                occurred_at,
+            },
+            ["transaction_recorded", beverage, sugar_amount, money_inserted, price_charged, change, outcome, ref serving @ ..] =>
+            {
+                let (cup_size, add_ons) = parse_serving_fields(serving)?;
+                MachineEvent::TransactionRecorded(LedgerEntry {
+                    beverage: parse_beverage(beverage)?,
+                    sugar_amount: parse_sugar_amount(sugar_amount)?,
+                    cup_size,
+                    add_ons,
+                    money_inserted: parse_number(money_inserted)?,
+                    price_charged: parse_number(price_charged)?,
+                    change: parse_number(change)?,
+                    outcome: parse_outcome(outcome)?,
+                    occurred_at,
+                })
+            }
+            ["purchases_report_printed"] => MachineEvent::PurchasesReportPrinted { occurred_at },
+            ["z_report_printed"] => MachineEvent::ZReportPrinted { occurred_at },
+            _ => return Err(unknown_event()),
+        };
+        Ok(machine_event)
+    }
+}
+
+impl std::fmt::Display for ParseMachineEventError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseMachineEventError::UnknownEvent(event) => {
+                write!(f, "unknown machine event \"{event}\"")
+            }
+            ParseMachineEventError::InvalidValue(value) => {
+                write!(f, "invalid machine event value \"{value}\"")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseMachineEventError {}
+
+impl From<InvalidValue> for ParseMachineEventError {
+    fn from(invalid_value: InvalidValue) -> Self {
+        ParseMachineEventError::InvalidValue(invalid_value.0)
+    }
+}
+
+#[cfg(test)]
+mod machine_event_tests {
+    use std::time::{Duration, SystemTime};
+
+    use test_case::test_case;
+
+    use super::{MachineEvent, ParseMachineEventError};
+    use crate::machine_system::{
+        beverages::{
+            add_on::AddOn,
+            beverage::{Beverage, ColdBeverageOption, HotBeverageOption},
+            cup_size::CupSize,
+            ingredient::Ingredient,
+            sugar_amount::{SugarAmount, Sweetener},
+        },
+        coins::Coin,
+        restock::RestockRecord,
+        sales_ledger::{LedgerEntry, TransactionOutcome},
+    };
+
+    fn at(seconds: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    #[test_case(MachineEvent::CoinInserted { coin: Coin::FiftyCents, occurred_at: at(1) } ; "coin inserted")]
+    #[test_case(MachineEvent::CreditCancelled { coins: vec![], occurred_at: at(2) } ; "empty credit cancelled")]
+    #[test_case(MachineEvent::PaymentAccepted { beverage: Beverage::Tea(HotBeverageOption::ExtraHot), inserted_coins: vec![Coin::OneEuro], change: vec![Coin::FiftyCents, Coin::TenCents], paid_with_credit: true, occurred_at: at(3) } ; "payment accepted")]
+    #[test_case(MachineEvent::BeverageServed { beverage: Beverage::OrangeJuice, sugar_amount: SugarAmount::Zero, cup_size: CupSize::Small, add_ons: vec![], occurred_at: at(4) } ; "beverage served")]
+    #[test_case(MachineEvent::ShortageDetected { beverage: Beverage::HotChocolate(HotBeverageOption::Standard), cup_size: CupSize::Large, occurred_at: at(5) } ; "shortage detected")]
+    #[test_case(MachineEvent::ShortageDetected { beverage: Beverage::new("lemonade", None), cup_size: CupSize::Medium, occurred_at: at(5) } ; "shortage of a catalog beverage")]
+    #[test_case(MachineEvent::BeverageServed { beverage: Beverage::new("espresso", Some(HotBeverageOption::ExtraHot)), sugar_amount: SugarAmount::One, cup_size: CupSize::Medium, add_ons: vec![AddOn::Milk, AddOn::ExtraShot], occurred_at: at(5) } ; "catalog beverage with an option served")]
+    #[test_case(MachineEvent::BeverageServed { beverage: Beverage::Tea(HotBeverageOption::Standard), sugar_amount: SugarAmount::Two.with_sweetener(Sweetener::SugarFree), cup_size: CupSize::Medium, add_ons: vec![], occurred_at: at(5) } ; "beverage served with sweetener")]
+    #[test_case(MachineEvent::BeverageServed { beverage: Beverage::Coffee(HotBeverageOption::Standard), sugar_amount: SugarAmount::stick_only(), cup_size: CupSize::Medium, add_ons: vec![], occurred_at: at(5) } ; "beverage served with a stick only")]
+    #[test_case(MachineEvent::ShortageDetected { beverage: Beverage::cold("orange_juice", ColdBeverageOption::WithIce), cup_size: CupSize::Medium, occurred_at: at(5) } ; "shortage of orange juice with ice")]
+    #[test_case(MachineEvent::ShortageDetected { beverage: Beverage::cold("lemonade", ColdBeverageOption::Chilled), cup_size: CupSize::Medium, occurred_at: at(5) } ; "shortage of a chilled catalog beverage")]
+    #[test_case(MachineEvent::PaymentRefunded { inserted_coins: vec![Coin::OneEuro], change: vec![Coin::TwentyCents, Coin::TwentyCents], occurred_at: at(6) } ; "payment refunded")]
+    #[test_case(MachineEvent::TransactionRecorded(LedgerEntry { beverage: Beverage::Coffee(HotBeverageOption::Standard), sugar_amount: SugarAmount::One, cup_size: CupSize::Large, add_ons: vec![AddOn::OatMilk], money_inserted: 100, price_charged: 60, change: 40, outcome: TransactionOutcome::Served, occurred_at: at(7) }) ; "transaction recorded")]
+    #[test_case(MachineEvent::ZReportPrinted { occurred_at: at(8) } ; "z report printed")]
+    #[test_case(MachineEvent::Restocked(RestockRecord { operator: String::from("Jane Doe"), ingredient: Ingredient::TeaBags, quantity: 50, restocked_at: at(9) }) ; "restocked by an operator with spaces in their name")]
+    #[test_case(MachineEvent::Restocked(RestockRecord { operator: String::from("Jane\nDoe \\ night shift\r"), ingredient: Ingredient::TeaBags, quantity: 50, restocked_at: at(9) }) ; "restocked by an operator with line breaks and backslashes in their name")]
+    fn events_round_trip_through_their_text_form(event: MachineEvent) {
+        assert_eq!(event.to_string().parse::<MachineEvent>(), Ok(event))
+    }
+
+    #[test]
+    fn events_recorded_without_a_cup_size_are_medium() {
+        assert_eq!(
+            "4000000000 beverage_served orange_juice 0".parse::<MachineEvent>(),
+            Ok(MachineEvent::BeverageServed {
+                beverage: Beverage::OrangeJuice,
+                sugar_amount: SugarAmount::Zero,
+                cup_size: CupSize::Medium,
+                add_ons: vec![],
+                occurred_at: at(4),
+            })
+        )
+    }
+
+    #[test]
+    fn reject_unknown_events() {
+        assert_eq!(
+            "0 machine_exploded".parse::<MachineEvent>(),
+            Err(ParseMachineEventError::UnknownEvent(String::from(
+                "0 machine_exploded"
+            )))
+        )
+    }
+}
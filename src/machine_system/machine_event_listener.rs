@@ -0,0 +1,6 @@
+use super::machine_event::MachineEvent;
+use crate::shared::MaybeSendSync;
+
+pub trait MachineEventListener: MaybeSendSync {
+    fn on_event(&self, event: &MachineEvent);
+}
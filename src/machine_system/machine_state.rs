@@ -0,0 +1,342 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+    str::FromStr,
+    time::SystemTime,
+};
+
+use super::{
+    beverages::{beverage::Beverage, cup_size::CupSize, ingredient::Ingredient},
+    coins::{Coin, CoinInventory},
+    record_format::{
+        beverage_name, coin_name, cup_size_name, ingredient_name, outcome_name, parse_beverage,
+        parse_coin, parse_cup_size, parse_ingredient, parse_number, parse_outcome,
+        parse_serving_fields, parse_sugar_amount, parse_timestamp, serving_fields,
+        sugar_amount_name, timestamp, InvalidValue,
+    },
+    sales_ledger::LedgerEntry,
+};
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MachineState {
+    pub coin_inventory: CoinInventory,
+    pub credit: Vec<Coin>,
+    pub ledger_entries: Vec<LedgerEntry>,
+    pub period_first_entry: usize,
+    pub period_started_at: SystemTime,
+    pub stock: HashMap<Ingredient, u32>,
+    pub beverages_in_shortage: HashSet<(Beverage, CupSize)>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ParseMachineStateError {
+    UnknownRecord(String),
+    InvalidValue(String),
+}
+
+impl MachineState {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut temporary_path = path.as_os_str().to_owned();
+        temporary_path.push(".tmp");
+
+        let written = File::create(&temporary_path).and_then(|mut file| {
+            file.write_all(self.to_string().as_bytes())?;
+            file.sync_all()
+        });
+        if let Err(error) = written {
+            let _ = fs::remove_file(&temporary_path);
+            return Err(error);
+        }
+        fs::rename(&temporary_path, path)?;
+        sync_parent_directory(path)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+#[cfg(unix)]
+fn sync_parent_directory(path: &Path) -> io::Result<()> {
+    let directory = match path.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory,
+        _ => Path::new("."),
+    };
+    File::open(directory)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_parent_directory(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+impl std::fmt::Display for MachineState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "period {} {}",
+            self.period_first_entry,
+            timestamp(self.period_started_at)
+        )?;
+        for coin in Coin::ALL {
+            let count = self.coin_inventory.count(&coin);
+            if count > 0 {
+                writeln!(f, "coins {} {count}", coin_name(&coin))?;
+            }
+        }
+        for coin in &self.credit {
+            writeln!(f, "credit {}", coin_name(coin))?;
+        }
+        for ingredient in Ingredient::ALL {
+            if let Some(quantity) = self.stock.get(&ingredient) {
+                writeln!(f, "stock {} {quantity}", ingredient_name(&ingredient))?;
+            }
+        }
+        let mut beverages_in_shortage: Vec<&(Beverage, CupSize)> =
+            self.beverages_in_shortage.iter().collect();
+        beverages_in_shortage.sort();
+        for (beverage, cup_size) in beverages_in_shortage {
+            writeln!(
+                f,
+                "shortage {} {}",
+                beverage_name(beverage),
+                cup_size_name(cup_size)
+            )?;
+        }
+        for entry in &self.ledger_entries {
+            writeln!(
+                f,
+                "entry {} {} {} {} {} {} {} {}",
+                beverage_name(&entry.beverage),
+                sugar_amount_name(&entry.sugar_amount),
+                entry.money_inserted,
+                entry.price_charged,
+                entry.change,
+                outcome_name(&entry.outcome),
+                timestamp(entry.occurred_at),
+                serving_fields(&entry.cup_size, &entry.add_ons)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MachineState {
+    type Err = ParseMachineStateError;
+
+    fn from_str(state: &str) -> Result<Self, Self::Err> {
+        let mut machine_state = MachineState {
+            coin_inventory: CoinInventory::default(),
+            credit: vec![],
+            ledger_entries: vec![],
+            period_first_entry: 0,
+            period_started_at: SystemTime::UNIX_EPOCH,
+            stock: HashMap::new(),
+            beverages_in_shortage: HashSet::new(),
+        };
+
+        for line in state.lines().filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields[..] {
+                ["period", first_entry, started_at] => {
+                    machine_state.period_first_entry = parse_number(first_entry)?;
+                    machine_state.period_started_at = parse_timestamp(started_at)?;
+                }
+                ["coins", coin, count] => {
+                    machine_state.coin_inventory = machine_state
+                        .coin_inventory
+                        .with_coins(parse_coin(coin)?, parse_number(count)?);
+                }
+                ["credit", coin] => machine_state.credit.push(parse_coin(coin)?),
+                ["stock", ingredient, quantity] => {
+                    machine_state
+                        .stock
+                        .insert(parse_ingredient(ingredient)?, parse_number(quantity)?);
+                }
+                ["shortage", beverage] => {
+                    machine_state
+                        .beverages_in_shortage
+                        .insert((parse_beverage(beverage)?, CupSize::default()));
+                }
+                ["shortage", beverage, cup_size] => {
+                    machine_state
+                        .beverages_in_shortage
+                        .insert((parse_beverage(beverage)?, parse_cup_size(cup_size)?));
+                }
+                ["entry", beverage, sugar_amount, money_inserted, price_charged, change, outcome, occurred_at, ref serving @ ..] =>
+                {
+                    let (cup_size, add_ons) = parse_serving_fields(serving)?;
+                    machine_state.ledger_entries.push(LedgerEntry {
+                        beverage: parse_beverage(beverage)?,
+                        sugar_amount: parse_sugar_amount(sugar_amount)?,
+                        cup_size,
+                        add_ons,
+                        money_inserted: parse_number(money_inserted)?,
+                        price_charged: parse_number(price_charged)?,
+                        change: parse_number(change)?,
+                        outcome: parse_outcome(outcome)?,
+                        occurred_at: parse_timestamp(occurred_at)?,
+                    })
+                }
+                _ => return Err(ParseMachineStateError::UnknownRecord(String::from(line))),
+            }
+        }
+
+        if machine_state.period_first_entry > machine_state.ledger_entries.len() {
+            return Err(ParseMachineStateError::InvalidValue(
+                machine_state.period_first_entry.to_string(),
+            ));
+        }
+        Ok(machine_state)
+    }
+}
+
+impl std::fmt::Display for ParseMachineStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseMachineStateError::UnknownRecord(record) => {
+                write!(f, "unknown machine state record \"{record}\"")
+            }
+            ParseMachineStateError::InvalidValue(value) => {
+                write!(f, "invalid machine state value \"{value}\"")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseMachineStateError {}
+
+impl From<InvalidValue> for ParseMachineStateError {
+    fn from(invalid_value: InvalidValue) -> Self {
+        ParseMachineStateError::InvalidValue(invalid_value.0)
+    }
+}
+
+#[cfg(test)]
+mod machine_state_tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        fs,
+        time::{Duration, SystemTime},
+    };
+
+    use super::{MachineState, ParseMachineStateError};
+    use crate::machine_system::{
+        beverages::{
+            add_on::AddOn,
+            beverage::{Beverage, HotBeverageOption},
+            cup_size::CupSize,
+            ingredient::Ingredient,
+            sugar_amount::SugarAmount,
+        },
+        coins::{Coin, CoinInventory},
+        sales_ledger::{LedgerEntry, TransactionOutcome},
+    };
+
+    fn machine_state() -> MachineState {
+        MachineState {
+            coin_inventory: CoinInventory::standard_float().with_coins(Coin::TwoEuros, 1),
+            credit: vec![Coin::FiftyCents, Coin::TenCents],
+            ledger_entries: vec![
+                LedgerEntry {
+                    beverage: Beverage::Tea(HotBeverageOption::ExtraHot),
+                    sugar_amount: SugarAmount::Two,
+                    cup_size: CupSize::Large,
+                    add_ons: vec![AddOn::Milk, AddOn::Decaf],
+                    money_inserted: 100,
+                    price_charged: 40,
+                    change: 60,
+                    outcome: TransactionOutcome::Served,
+                    occurred_at: SystemTime::UNIX_EPOCH + Duration::from_millis(1500),
+                },
+                LedgerEntry {
+                    beverage: Beverage::OrangeJuice,
+                    sugar_amount: SugarAmount::Zero,
+                    cup_size: CupSize::Medium,
+                    add_ons: vec![],
+                    money_inserted: 100,
+                    price_charged: 0,
+                    change: 0,
+                    outcome: TransactionOutcome::ShortageRefund,
+                    occurred_at: SystemTime::UNIX_EPOCH + Duration::from_secs(60),
+                },
+            ],
+            period_first_entry: 1,
+            period_started_at: SystemTime::UNIX_EPOCH + Duration::from_secs(30),
+            stock: HashMap::from([(Ingredient::TeaBags, 12), (Ingredient::WaterMl, 900)]),
+            beverages_in_shortage: HashSet::from([
+                (Beverage::OrangeJuice, CupSize::Medium),
+                (Beverage::Tea(HotBeverageOption::Standard), CupSize::Large),
+            ]),
+        }
+    }
+
+    #[test]
+    fn machine_state_round_trips_through_its_text_form() {
+        let state = machine_state();
+
+        assert_eq!(state.to_string().parse::<MachineState>(), Ok(state))
+    }
+
+    #[test]
+    fn entries_saved_without_a_cup_size_are_medium() {
+        let state: MachineState = "entry coffee 0 60 60 0 served 0".parse().unwrap();
+
+        assert_eq!(state.ledger_entries[0].cup_size, CupSize::Medium)
+    }
+
+    #[test]
+    fn reject_unknown_records() {
+        assert_eq!(
+            "cups 12".parse::<MachineState>(),
+            Err(ParseMachineStateError::UnknownRecord(String::from(
+                "cups 12"
+            )))
+        )
+    }
+
+    #[test]
+    fn save_and_load_a_snapshot_file() {
+        let directory = std::env::temp_dir().join(format!(
+            "machine_state_tests_{}_save_and_load",
+            std::process::id()
+        ));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("machine.state");
+        let state = machine_state();
+
+        state.save(&path).unwrap();
+
+        assert_eq!(MachineState::load(&path).unwrap(), state);
+        assert!(!directory.join("machine.state.tmp").exists());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn keep_the_previous_snapshot_when_a_save_cannot_complete() {
+        let directory = std::env::temp_dir().join(format!(
+            "machine_state_tests_{}_interrupted_save",
+            std::process::id()
+        ));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("machine.state");
+        let state = machine_state();
+        state.save(&path).unwrap();
+        fs::create_dir(directory.join("machine.state.tmp")).unwrap();
+
+        let saved = MachineState {
+            credit: vec![],
+            ..machine_state()
+        }
+        .save(&path);
+
+        assert!(saved.is_err());
+        assert_eq!(MachineState::load(&path).unwrap(), state);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -1,5 +1,16 @@
-use super::beverages::beverage::Beverage;
+use std::{io, time::SystemTime};
 
-pub trait Notifier {
-    fn notify_missing_beverage(&self, beverage: &Beverage);
+use super::{
+    beverages::{beverage::Beverage, beverage_server::ServingFailure, ingredient::Ingredient},
+    machine_event::MachineEvent,
+};
+use crate::shared::MaybeSendSync;
+
+pub trait Notifier: MaybeSendSync {
+    fn notify_missing_beverage(&self, beverage: &Beverage, detected_at: SystemTime);
+    fn notify_missing_ingredient(&self, ingredient: &Ingredient, detected_at: SystemTime);
+    fn notify_serving_failure(&self, beverage: &Beverage, serving_failure: &ServingFailure);
+    fn notify_low_stock(&self, beverage: &Beverage, remaining_servings: u32);
+    fn notify_low_ingredient_stock(&self, ingredient: &Ingredient, remaining_quantity: u32);
+    fn notify_event_store_failure(&self, event: &MachineEvent, error: &io::Error);
 }
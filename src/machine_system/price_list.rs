@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use super::beverages::{
+    add_on::AddOn, beverage::Beverage, beverage_catalog::BeverageCatalog, cup_size::CupSize,
+};
+
+/// Catalog prices are kept apart from the entries set with the `with_*`
+/// methods, so that overriding a price list only replaces what was set.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct PriceList {
+    catalog_prices: HashMap<Beverage, u32>,
+    prices: HashMap<Beverage, u32>,
+    cup_size_adjustments: HashMap<CupSize, i32>,
+    add_on_surcharges: HashMap<AddOn, u32>,
+}
+
+impl PriceList {
+    pub fn empty() -> Self {
+        Self {
+            catalog_prices: HashMap::new(),
+            prices: HashMap::new(),
+            cup_size_adjustments: HashMap::new(),
+            add_on_surcharges: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn with_catalog_price(mut self, beverage: Beverage, price: u32) -> Self {
+        self.catalog_prices.insert(beverage.normalized(), price);
+        self
+    }
+
+    pub fn with_price(mut self, beverage: Beverage, price: u32) -> Self {
+        self.prices.insert(beverage.normalized(), price);
+        self
+    }
+
+    pub fn with_cup_size_adjustment(mut self, cup_size: CupSize, adjustment: i32) -> Self {
+        self.cup_size_adjustments.insert(cup_size, adjustment);
+        self
+    }
+
+    pub fn with_add_on_surcharge(mut self, add_on: AddOn, surcharge: u32) -> Self {
+        self.add_on_surcharges.insert(add_on, surcharge);
+        self
+    }
+
+    /// Keeps these prices and replaces only the prices, cup size adjustments
+    /// and add-on surcharges that were set on `overrides`.
+    pub fn overridden_by(mut self, overrides: PriceList) -> Self {
+        self.prices.extend(overrides.prices);
+        self.cup_size_adjustments
+            .extend(overrides.cup_size_adjustments);
+        self.add_on_surcharges.extend(overrides.add_on_surcharges);
+        self
+    }
+
+    pub fn price_of(&self, beverage: &Beverage) -> Option<u32> {
+        let beverage = beverage.normalized();
+        let plain_beverage = beverage.without_cold_option();
+        [&self.prices, &self.catalog_prices]
+            .into_iter()
+            .find_map(|prices| {
+                prices
+                    .get(&beverage)
+                    .or_else(|| prices.get(&plain_beverage))
+            })
+            .copied()
+    }
+
+    pub fn price_of_size(&self, beverage: &Beverage, cup_size: &CupSize) -> Option<u32> {
+        let adjustment = self
+            .cup_size_adjustments
+            .get(cup_size)
+            .copied()
+            .unwrap_or_else(|| default_cup_size_adjustment(cup_size));
+        Some(self.price_of(beverage)?.saturating_add_signed(adjustment))
+    }
+
+    pub fn price_with_add_ons(
+        &self,
+        beverage: &Beverage,
+        cup_size: &CupSize,
+        add_ons: &[AddOn],
+    ) -> Option<u32> {
+        let surcharges: u32 = AddOn::distinct(add_ons)
+            .map(|add_on| {
+                self.add_on_surcharges
+                    .get(&add_on)
+                    .copied()
+                    .unwrap_or_else(|| default_add_on_surcharge(&add_on))
+            })
+            .sum();
+        Some(self.price_of_size(beverage, cup_size)? + surcharges)
+    }
+}
+
+fn default_cup_size_adjustment(cup_size: &CupSize) -> i32 {
+    match cup_size {
+        CupSize::Small => -10,
+        CupSize::Medium => 0,
+        CupSize::Large => 20,
+    }
+}
+
+fn default_add_on_surcharge(add_on: &AddOn) -> u32 {
+    match add_on {
+        AddOn::Milk => 10,
+        AddOn::OatMilk => 20,
+        AddOn::ExtraShot => 30,
+        AddOn::Decaf => 5,
+        AddOn::WhippedCream => 15,
+    }
+}
+
+impl Default for PriceList {
+    fn default() -> Self {
+        BeverageCatalog::default().price_list()
+    }
+}
+
+#[cfg(test)]
+mod price_list_tests {
+    use super::PriceList;
+    use crate::machine_system::beverages::{
+        add_on::AddOn,
+        beverage::{Beverage, HotBeverageOption},
+        cup_size::CupSize,
+    };
+
+    #[test]
+    fn overriding_keeps_every_entry_the_overrides_did_not_set() {
+        let coffee = Beverage::Coffee(HotBeverageOption::Standard);
+        let tea = Beverage::Tea(HotBeverageOption::Standard);
+        let base = PriceList::empty()
+            .with_catalog_price(coffee.clone(), 80)
+            .with_catalog_price(tea.clone(), 40)
+            .with_add_on_surcharge(AddOn::Milk, 12);
+        let overrides = PriceList::default()
+            .with_price(tea.clone(), 45)
+            .with_cup_size_adjustment(CupSize::Large, 30);
+
+        let price_list = base.overridden_by(overrides);
+
+        assert_eq!(price_list.price_of(&coffee), Some(80));
+        assert_eq!(price_list.price_of(&tea), Some(45));
+        assert_eq!(
+            price_list.price_of_size(&coffee, &CupSize::Large),
+            Some(110)
+        );
+        assert_eq!(price_list.price_of_size(&coffee, &CupSize::Small), Some(70));
+        assert_eq!(
+            price_list.price_with_add_ons(&coffee, &CupSize::Medium, &[AddOn::Milk, AddOn::Decaf]),
+            Some(97)
+        )
+    }
+}
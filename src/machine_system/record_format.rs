@@ -0,0 +1,303 @@
+use std::{
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use super::{
+    beverages::{
+        add_on::AddOn,
+        beverage::{Beverage, ColdBeverageOption, HotBeverageOption},
+        beverage_catalog::BeverageCatalog,
+        cup_size::CupSize,
+        ingredient::Ingredient,
+        sugar_amount::{SugarAmount, Sweetener},
+    },
+    coins::Coin,
+    sales_ledger::TransactionOutcome,
+};
+
+const EMPTY_LIST: &str = "-";
+const CUSTOM_BEVERAGE_PREFIX: &str = "custom/";
+const COLD_BEVERAGE_PREFIX: &str = "cold/";
+const CUSTOM_BEVERAGE_OPTION_SEPARATOR: char = '/';
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub(crate) struct InvalidValue(pub(crate) String);
+
+pub(crate) fn timestamp(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+pub(crate) fn parse_timestamp(value: &str) -> Result<SystemTime, InvalidValue> {
+    Ok(SystemTime::UNIX_EPOCH + Duration::from_nanos(parse_number(value)?))
+}
+
+pub(crate) fn parse_number<T: FromStr>(value: &str) -> Result<T, InvalidValue> {
+    value.parse().map_err(|_| InvalidValue(String::from(value)))
+}
+
+const COIN_NAMES: [(Coin, &str); 6] = [
+    (Coin::FiveCents, "5c"),
+    (Coin::TenCents, "10c"),
+    (Coin::TwentyCents, "20c"),
+    (Coin::FiftyCents, "50c"),
+    (Coin::OneEuro, "1e"),
+    (Coin::TwoEuros, "2e"),
+];
+
+const INGREDIENT_NAMES: [(Ingredient, &str); 14] = [
+    (Ingredient::CoffeeGrams, "coffee_grams"),
+    (Ingredient::TeaBags, "tea_bags"),
+    (Ingredient::ChocolatePowderGrams, "chocolate_powder_grams"),
+    (Ingredient::OrangeJuiceMl, "orange_juice_ml"),
+    (Ingredient::WaterMl, "water_ml"),
+    (Ingredient::SugarDoses, "sugar_doses"),
+    (Ingredient::SweetenerDoses, "sweetener_doses"),
+    (Ingredient::StirringSticks, "stirring_sticks"),
+    (Ingredient::MilkMl, "milk_ml"),
+    (Ingredient::OatMilkMl, "oat_milk_ml"),
+    (Ingredient::DecafCoffeeGrams, "decaf_coffee_grams"),
+    (Ingredient::WhippedCreamGrams, "whipped_cream_grams"),
+    (Ingredient::ColdWaterMl, "cold_water_ml"),
+    (Ingredient::IceCubes, "ice_cubes"),
+];
+
+const HOT_BEVERAGE_OPTION_NAMES: [(HotBeverageOption, &str); 2] = [
+    (HotBeverageOption::Standard, "standard"),
+    (HotBeverageOption::ExtraHot, "extra_hot"),
+];
+
+const COLD_BEVERAGE_OPTION_NAMES: [(ColdBeverageOption, &str); 3] = [
+    (ColdBeverageOption::NoIce, "no_ice"),
+    (ColdBeverageOption::WithIce, "with_ice"),
+    (ColdBeverageOption::Chilled, "chilled"),
+];
+
+const SUGAR_AMOUNT_FLAG_SEPARATOR: char = '/';
+const SUGAR_FREE_FLAG: &str = "sugar_free";
+const STICK_ONLY_FLAG: &str = "stick";
+
+const CUP_SIZE_NAMES: [(CupSize, &str); 3] = [
+    (CupSize::Small, "small"),
+    (CupSize::Medium, "medium"),
+    (CupSize::Large, "large"),
+];
+
+const ADD_ON_NAMES: [(AddOn, &str); 5] = [
+    (AddOn::Milk, "milk"),
+    (AddOn::OatMilk, "oat_milk"),
+    (AddOn::ExtraShot, "extra_shot"),
+    (AddOn::Decaf, "decaf"),
+    (AddOn::WhippedCream, "whipped_cream"),
+];
+
+const OUTCOME_NAMES: [(TransactionOutcome, &str); 6] = [
+    (TransactionOutcome::Served, "served"),
+    (TransactionOutcome::Underpaid, "underpaid"),
+    (TransactionOutcome::ExactChangeOnly, "exact_change_only"),
+    (TransactionOutcome::ShortageRefund, "shortage_refund"),
+    (
+        TransactionOutcome::ServingFailureRefund,
+        "serving_failure_refund",
+    ),
+    (TransactionOutcome::Rejected, "rejected"),
+];
+
+fn name_of<T: PartialEq>(names: &[(T, &'static str)], value: &T) -> &'static str {
+    names
+        .iter()
+        .find(|(known, _)| known == value)
+        .map(|(_, name)| *name)
+        .expect("every value has a persisted name")
+}
+
+fn parse_name<T: Clone>(names: &[(T, &str)], name: &str) -> Result<T, InvalidValue> {
+    names
+        .iter()
+        .find(|(_, known)| *known == name)
+        .map(|(value, _)| value.clone())
+        .ok_or_else(|| InvalidValue(String::from(name)))
+}
+
+pub(crate) fn coin_name(coin: &Coin) -> &'static str {
+    name_of(&COIN_NAMES, coin)
+}
+
+pub(crate) fn parse_coin(name: &str) -> Result<Coin, InvalidValue> {
+    parse_name(&COIN_NAMES, name)
+}
+
+pub(crate) fn ingredient_name(ingredient: &Ingredient) -> &'static str {
+    name_of(&INGREDIENT_NAMES, ingredient)
+}
+
+pub(crate) fn parse_ingredient(name: &str) -> Result<Ingredient, InvalidValue> {
+    parse_name(&INGREDIENT_NAMES, name)
+}
+
+pub(crate) fn cup_size_name(cup_size: &CupSize) -> &'static str {
+    name_of(&CUP_SIZE_NAMES, cup_size)
+}
+
+pub(crate) fn parse_cup_size(name: &str) -> Result<CupSize, InvalidValue> {
+    parse_name(&CUP_SIZE_NAMES, name)
+}
+
+pub(crate) fn beverage_name(beverage: &Beverage) -> String {
+    match beverage {
+        Beverage::Custom { id, option: None } => format!("{CUSTOM_BEVERAGE_PREFIX}{id}"),
+        Beverage::Custom {
+            id,
+            option: Some(option),
+        } => format!(
+            "{CUSTOM_BEVERAGE_PREFIX}{id}{CUSTOM_BEVERAGE_OPTION_SEPARATOR}{}",
+            name_of(&HOT_BEVERAGE_OPTION_NAMES, option)
+        ),
+        Beverage::Cold { id, option } => format!(
+            "{COLD_BEVERAGE_PREFIX}{id}{CUSTOM_BEVERAGE_OPTION_SEPARATOR}{}",
+            name_of(&COLD_BEVERAGE_OPTION_NAMES, option)
+        ),
+        known_beverage => known_beverage.name(),
+    }
+}
+
+pub(crate) fn parse_beverage(name: &str) -> Result<Beverage, InvalidValue> {
+    if let Some(cold_beverage) = name.strip_prefix(COLD_BEVERAGE_PREFIX) {
+        let (id, option) = cold_beverage
+            .split_once(CUSTOM_BEVERAGE_OPTION_SEPARATOR)
+            .ok_or_else(|| InvalidValue(String::from(name)))?;
+        return Ok(Beverage::cold(
+            id,
+            parse_name(&COLD_BEVERAGE_OPTION_NAMES, option)?,
+        ));
+    }
+    let Some(custom_beverage) = name.strip_prefix(CUSTOM_BEVERAGE_PREFIX) else {
+        return BeverageCatalog::default()
+            .beverages()
+            .into_iter()
+            .find(|known_beverage| known_beverage.name() == name)
+            .ok_or_else(|| InvalidValue(String::from(name)));
+    };
+    match custom_beverage.split_once(CUSTOM_BEVERAGE_OPTION_SEPARATOR) {
+        Some((id, option)) => Ok(Beverage::new(
+            id,
+            Some(parse_name(&HOT_BEVERAGE_OPTION_NAMES, option)?),
+        )),
+        None => Ok(Beverage::new(custom_beverage, None)),
+    }
+}
+
+pub(crate) fn sugar_amount_name(sugar_amount: &SugarAmount) -> String {
+    let mut name = sugar_amount.dose_count().to_string();
+    if sugar_amount.sweetener() == Sweetener::SugarFree {
+        name.push(SUGAR_AMOUNT_FLAG_SEPARATOR);
+        name.push_str(SUGAR_FREE_FLAG);
+    }
+    if sugar_amount.dose_count() == 0 && sugar_amount.has_stick() {
+        name.push(SUGAR_AMOUNT_FLAG_SEPARATOR);
+        name.push_str(STICK_ONLY_FLAG);
+    }
+    name
+}
+
+pub(crate) fn parse_sugar_amount(name: &str) -> Result<SugarAmount, InvalidValue> {
+    let mut fields = name.split(SUGAR_AMOUNT_FLAG_SEPARATOR);
+    let doses = parse_number(fields.next().unwrap_or_default())?;
+    let mut sugar_amount = SugarAmount::doses(doses);
+    for flag in fields {
+        sugar_amount = match flag {
+            SUGAR_FREE_FLAG => sugar_amount.with_sweetener(Sweetener::SugarFree),
+            STICK_ONLY_FLAG => sugar_amount.with_stick(),
+            _ => return Err(InvalidValue(String::from(name))),
+        };
+    }
+    Ok(sugar_amount)
+}
+
+pub(crate) fn serving_fields(cup_size: &CupSize, add_ons: &[AddOn]) -> String {
+    let add_ons_field = if add_ons.is_empty() {
+        String::from(EMPTY_LIST)
+    } else {
+        add_ons
+            .iter()
+            .map(|add_on| name_of(&ADD_ON_NAMES, add_on))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    format!("{} {add_ons_field}", name_of(&CUP_SIZE_NAMES, cup_size))
+}
+
+pub(crate) fn parse_serving_fields(fields: &[&str]) -> Result<(CupSize, Vec<AddOn>), InvalidValue> {
+    let (cup_size, add_ons_field) = match fields {
+        [] => return Ok((CupSize::default(), vec![])),
+        [cup_size] => (parse_name(&CUP_SIZE_NAMES, cup_size)?, EMPTY_LIST),
+        [cup_size, add_ons] => (parse_name(&CUP_SIZE_NAMES, cup_size)?, *add_ons),
+        _ => return Err(InvalidValue(fields.join(" "))),
+    };
+    let add_ons = if add_ons_field == EMPTY_LIST {
+        vec![]
+    } else {
+        add_ons_field
+            .split(',')
+            .map(|name| parse_name(&ADD_ON_NAMES, name))
+            .collect::<Result<_, _>>()?
+    };
+    Ok((cup_size, add_ons))
+}
+
+pub(crate) fn outcome_name(outcome: &TransactionOutcome) -> &'static str {
+    name_of(&OUTCOME_NAMES, outcome)
+}
+
+pub(crate) fn parse_outcome(name: &str) -> Result<TransactionOutcome, InvalidValue> {
+    parse_name(&OUTCOME_NAMES, name)
+}
+
+pub(crate) fn escape_text(text: &str) -> String {
+    let mut escaped = String::new();
+    for character in text.chars() {
+        match character {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+pub(crate) fn unescape_text(escaped: &str) -> Result<String, InvalidValue> {
+    let mut text = String::new();
+    let mut characters = escaped.chars();
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            text.push(character);
+            continue;
+        }
+        match characters.next() {
+            Some('\\') => text.push('\\'),
+            Some('n') => text.push('\n'),
+            Some('r') => text.push('\r'),
+            _ => return Err(InvalidValue(String::from(escaped))),
+        }
+    }
+    Ok(text)
+}
+
+pub(crate) fn coins_field(coins: &[Coin]) -> String {
+    if coins.is_empty() {
+        String::from(EMPTY_LIST)
+    } else {
+        coins.iter().map(coin_name).collect::<Vec<_>>().join(",")
+    }
+}
+
+pub(crate) fn parse_coins_field(field: &str) -> Result<Vec<Coin>, InvalidValue> {
+    if field == EMPTY_LIST {
+        Ok(vec![])
+    } else {
+        field.split(',').map(parse_coin).collect()
+    }
+}
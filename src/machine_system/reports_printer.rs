@@ -1,22 +1,143 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::SystemTime};
 
-use super::beverages::beverage::Beverage;
+use super::{
+    beverages::{
+        beverage::{Beverage, HotBeverageOption},
+        cup_size::CupSize,
+    },
+    restock::RestockRecord,
+    sales_ledger::{self, LedgerEntry, TransactionOutcome},
+};
+use crate::shared::MaybeSendSync;
 
-pub trait ReportsPrinter {
+pub trait ReportsPrinter: MaybeSendSync {
     fn print(&self, purchase_report: PurchasesReport);
+    fn print_restock_report(&self, restock_report: RestockReport);
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct PurchasesReport {
     pub beverages_quantities: HashMap<Beverage, u32>,
+    pub beverages_revenues: HashMap<Beverage, u32>,
     pub total_money_earned: u32,
+    pub hot_options_quantities: HashMap<HotBeverageOption, u32>,
+    pub cup_sizes_quantities: HashMap<CupSize, u32>,
+    pub sugar_doses_used: u32,
+    pub stirring_sticks_used: u32,
+    pub underpaid_attempts: HashMap<Beverage, u32>,
+    pub shortage_attempts: HashMap<Beverage, u32>,
+    pub period_started_at: SystemTime,
+    pub period_ended_at: SystemTime,
 }
 
 impl PurchasesReport {
-    pub fn new(beverages_quantities: &HashMap<Beverage, u32>, total_money_earned: u32) -> Self {
+    pub fn new(
+        beverages_quantities: &HashMap<Beverage, u32>,
+        beverages_revenues: &HashMap<Beverage, u32>,
+        total_money_earned: u32,
+        period_started_at: SystemTime,
+        period_ended_at: SystemTime,
+    ) -> Self {
         Self {
             beverages_quantities: beverages_quantities.clone(),
+            beverages_revenues: beverages_revenues.clone(),
             total_money_earned,
+            hot_options_quantities: HashMap::new(),
+            cup_sizes_quantities: HashMap::new(),
+            sugar_doses_used: 0,
+            stirring_sticks_used: 0,
+            underpaid_attempts: HashMap::new(),
+            shortage_attempts: HashMap::new(),
+            period_started_at,
+            period_ended_at,
+        }
+    }
+
+    pub fn from_ledger_entries(
+        entries: &[LedgerEntry],
+        period_started_at: SystemTime,
+        period_ended_at: SystemTime,
+    ) -> Self {
+        Self {
+            beverages_quantities: sales_ledger::beverages_quantities(entries),
+            beverages_revenues: sales_ledger::beverages_revenues(entries),
+            total_money_earned: sales_ledger::total_money_earned(entries),
+            hot_options_quantities: sales_ledger::hot_options_quantities(entries),
+            cup_sizes_quantities: sales_ledger::cup_sizes_quantities(entries),
+            sugar_doses_used: sales_ledger::sugar_doses_used(entries),
+            stirring_sticks_used: sales_ledger::stirring_sticks_used(entries),
+            underpaid_attempts: sales_ledger::attempts_with_outcome(
+                entries,
+                &TransactionOutcome::Underpaid,
+            ),
+            shortage_attempts: sales_ledger::attempts_with_outcome(
+                entries,
+                &TransactionOutcome::ShortageRefund,
+            ),
+            period_started_at,
+            period_ended_at,
+        }
+    }
+
+    pub fn sorted_beverages(&self) -> Vec<&Beverage> {
+        let mut beverages: Vec<&Beverage> = self
+            .beverages_quantities
+            .keys()
+            .chain(self.underpaid_attempts.keys())
+            .chain(self.shortage_attempts.keys())
+            .collect();
+        beverages.sort();
+        beverages.dedup();
+        beverages
+    }
+
+    pub fn quantity_of(&self, beverage: &Beverage) -> u32 {
+        self.beverages_quantities
+            .get(beverage)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn revenue_of(&self, beverage: &Beverage) -> u32 {
+        self.beverages_revenues.get(beverage).copied().unwrap_or(0)
+    }
+
+    pub fn underpaid_attempts_of(&self, beverage: &Beverage) -> u32 {
+        self.underpaid_attempts.get(beverage).copied().unwrap_or(0)
+    }
+
+    pub fn shortage_attempts_of(&self, beverage: &Beverage) -> u32 {
+        self.shortage_attempts.get(beverage).copied().unwrap_or(0)
+    }
+
+    pub fn quantity_with_option(&self, option: &HotBeverageOption) -> u32 {
+        self.hot_options_quantities
+            .get(option)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn quantity_of_size(&self, cup_size: &CupSize) -> u32 {
+        self.cup_sizes_quantities
+            .get(cup_size)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn total_quantity(&self) -> u32 {
+        self.beverages_quantities.values().sum()
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RestockReport {
+    pub restocks: Vec<RestockRecord>,
+}
+
+impl RestockReport {
+    pub fn new(restocks: &[RestockRecord]) -> Self {
+        Self {
+            restocks: restocks.to_vec(),
         }
     }
 }
@@ -0,0 +1,22 @@
+use std::time::SystemTime;
+
+use super::beverages::ingredient::Ingredient;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RestockRecord {
+    pub operator: String,
+    pub ingredient: Ingredient,
+    pub quantity: u32,
+    pub restocked_at: SystemTime,
+}
+
+#[derive(Default)]
+pub(crate) struct RestockHistory {
+    pub(crate) records: Vec<RestockRecord>,
+}
+
+impl RestockHistory {
+    pub(crate) fn record_restock(&mut self, restock_record: RestockRecord) {
+        self.records.push(restock_record);
+    }
+}
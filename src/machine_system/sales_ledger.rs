@@ -0,0 +1,238 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use super::beverages::{
+    add_on::AddOn,
+    beverage::{Beverage, HotBeverageOption},
+    cup_size::CupSize,
+    sugar_amount::SugarAmount,
+};
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum TransactionOutcome {
+    Served,
+    Underpaid,
+    ExactChangeOnly,
+    ShortageRefund,
+    ServingFailureRefund,
+    Rejected,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct LedgerEntry {
+    pub beverage: Beverage,
+    pub sugar_amount: SugarAmount,
+    pub cup_size: CupSize,
+    pub add_ons: Vec<AddOn>,
+    pub money_inserted: u32,
+    pub price_charged: u32,
+    pub change: u32,
+    pub outcome: TransactionOutcome,
+    pub occurred_at: SystemTime,
+}
+
+#[derive(Default)]
+pub struct SalesLedger {
+    entries: Vec<LedgerEntry>,
+}
+
+impl SalesLedger {
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn between(&self, from: SystemTime, to: SystemTime) -> Vec<&LedgerEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| from <= entry.occurred_at && entry.occurred_at < to)
+            .collect()
+    }
+
+    pub fn for_beverage(&self, beverage: &Beverage) -> Vec<&LedgerEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.beverage == *beverage)
+            .collect()
+    }
+
+    pub(crate) fn restore(entries: Vec<LedgerEntry>) -> Self {
+        Self { entries }
+    }
+
+    pub(crate) fn record(&mut self, entry: LedgerEntry) {
+        self.entries.push(entry);
+    }
+
+    pub(crate) fn entries_since(&self, first_entry: usize) -> &[LedgerEntry] {
+        &self.entries[first_entry..]
+    }
+}
+
+pub fn beverages_quantities<'a>(
+    entries: impl IntoIterator<Item = &'a LedgerEntry>,
+) -> HashMap<Beverage, u32> {
+    let mut quantities = HashMap::new();
+    for entry in served(entries) {
+        *quantities.entry(entry.beverage.clone()).or_insert(0) += 1;
+    }
+    quantities
+}
+
+pub fn beverages_revenues<'a>(
+    entries: impl IntoIterator<Item = &'a LedgerEntry>,
+) -> HashMap<Beverage, u32> {
+    let mut revenues = HashMap::new();
+    for entry in served(entries) {
+        *revenues.entry(entry.beverage.clone()).or_insert(0) += entry.price_charged;
+    }
+    revenues
+}
+
+pub fn total_money_earned<'a>(entries: impl IntoIterator<Item = &'a LedgerEntry>) -> u32 {
+    served(entries).map(|entry| entry.price_charged).sum()
+}
+
+pub fn hot_options_quantities<'a>(
+    entries: impl IntoIterator<Item = &'a LedgerEntry>,
+) -> HashMap<HotBeverageOption, u32> {
+    let mut quantities = HashMap::new();
+    for entry in served(entries) {
+        let Some(option) = entry.beverage.option() else {
+            continue;
+        };
+        *quantities.entry(option.clone()).or_insert(0) += 1;
+    }
+    quantities
+}
+
+pub fn cup_sizes_quantities<'a>(
+    entries: impl IntoIterator<Item = &'a LedgerEntry>,
+) -> HashMap<CupSize, u32> {
+    let mut quantities = HashMap::new();
+    for entry in served(entries) {
+        *quantities.entry(entry.cup_size).or_insert(0) += 1;
+    }
+    quantities
+}
+
+pub fn sugar_doses_used<'a>(entries: impl IntoIterator<Item = &'a LedgerEntry>) -> u32 {
+    served(entries)
+        .map(|entry| entry.sugar_amount.sugar_doses())
+        .sum()
+}
+
+pub fn stirring_sticks_used<'a>(entries: impl IntoIterator<Item = &'a LedgerEntry>) -> u32 {
+    served(entries)
+        .filter(|entry| entry.sugar_amount.has_stick())
+        .count() as u32
+}
+
+pub fn attempts_with_outcome<'a>(
+    entries: impl IntoIterator<Item = &'a LedgerEntry>,
+    outcome: &TransactionOutcome,
+) -> HashMap<Beverage, u32> {
+    let mut attempts = HashMap::new();
+    for entry in entries
+        .into_iter()
+        .filter(|entry| entry.outcome == *outcome)
+    {
+        *attempts.entry(entry.beverage.clone()).or_insert(0) += 1;
+    }
+    attempts
+}
+
+fn served<'a>(
+    entries: impl IntoIterator<Item = &'a LedgerEntry>,
+) -> impl Iterator<Item = &'a LedgerEntry> {
+    entries
+        .into_iter()
+        .filter(|entry| entry.outcome == TransactionOutcome::Served)
+}
+
+#[cfg(test)]
+mod sales_ledger_tests {
+    use std::{
+        collections::HashMap,
+        time::{Duration, SystemTime},
+    };
+
+    use super::{
+        beverages_quantities, beverages_revenues, total_money_earned, LedgerEntry, SalesLedger,
+        TransactionOutcome,
+    };
+    use crate::machine_system::beverages::{
+        beverage::{Beverage, HotBeverageOption},
+        cup_size::CupSize,
+        sugar_amount::SugarAmount,
+    };
+
+    fn entry(beverage: Beverage, outcome: TransactionOutcome, minute: u64) -> LedgerEntry {
+        let price_charged = match outcome {
+            TransactionOutcome::Served => 40,
+            _ => 0,
+        };
+        LedgerEntry {
+            beverage,
+            sugar_amount: SugarAmount::Zero,
+            cup_size: CupSize::Medium,
+            add_ons: vec![],
+            money_inserted: 100,
+            price_charged,
+            change: 100 - price_charged,
+            outcome,
+            occurred_at: SystemTime::UNIX_EPOCH + Duration::from_secs(minute * 60),
+        }
+    }
+
+    fn ledger() -> SalesLedger {
+        let mut ledger = SalesLedger::default();
+        let tea = Beverage::Tea(HotBeverageOption::Standard);
+        ledger.record(entry(tea.clone(), TransactionOutcome::Served, 0));
+        ledger.record(entry(Beverage::OrangeJuice, TransactionOutcome::Served, 10));
+        ledger.record(entry(tea.clone(), TransactionOutcome::ShortageRefund, 20));
+        ledger.record(entry(tea, TransactionOutcome::Served, 30));
+        ledger
+    }
+
+    #[test]
+    fn query_entries_by_time_range() {
+        let ledger = ledger();
+
+        let entries = ledger.between(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(10 * 60),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(30 * 60),
+        );
+
+        assert_eq!(entries, vec![&ledger.entries()[1], &ledger.entries()[2]])
+    }
+
+    #[test]
+    fn query_entries_by_beverage() {
+        let ledger = ledger();
+
+        let entries = ledger.for_beverage(&Beverage::OrangeJuice);
+
+        assert_eq!(entries, vec![&ledger.entries()[1]])
+    }
+
+    #[test]
+    fn derive_aggregates_from_served_entries_only() {
+        let ledger = ledger();
+
+        assert_eq!(
+            beverages_quantities(ledger.entries()),
+            HashMap::from([
+                (Beverage::Tea(HotBeverageOption::Standard), 2),
+                (Beverage::OrangeJuice, 1)
+            ])
+        );
+        assert_eq!(
+            beverages_revenues(ledger.entries()),
+            HashMap::from([
+                (Beverage::Tea(HotBeverageOption::Standard), 80),
+                (Beverage::OrangeJuice, 40)
+            ])
+        );
+        assert_eq!(total_money_earned(ledger.entries()), 120);
+        assert_eq!(total_money_earned(ledger.entries_since(2)), 40)
+    }
+}
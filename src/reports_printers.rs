@@ -0,0 +1,40 @@
+use std::time::SystemTime;
+
+use crate::machine_system::beverages::{beverage::Beverage, ingredient::Ingredient};
+
+pub mod csv_reports_printer;
+pub mod json_reports_printer;
+pub mod text_reports_printer;
+
+fn beverage_name(beverage: &Beverage) -> String {
+    beverage.name().replace('_', " ")
+}
+
+fn ingredient_name(ingredient: &Ingredient) -> &'static str {
+    match ingredient {
+        Ingredient::CoffeeGrams => "coffee grams",
+        Ingredient::TeaBags => "tea bags",
+        Ingredient::ChocolatePowderGrams => "chocolate powder grams",
+        Ingredient::OrangeJuiceMl => "orange juice ml",
+        Ingredient::WaterMl => "water ml",
+        Ingredient::SugarDoses => "sugar doses",
+        Ingredient::SweetenerDoses => "sweetener doses",
+        Ingredient::StirringSticks => "stirring sticks",
+        Ingredient::MilkMl => "milk ml",
+        Ingredient::OatMilkMl => "oat milk ml",
+        Ingredient::DecafCoffeeGrams => "decaf coffee grams",
+        Ingredient::WhippedCreamGrams => "whipped cream grams",
+        Ingredient::ColdWaterMl => "cold water ml",
+        Ingredient::IceCubes => "ice cubes",
+    }
+}
+
+fn format_euros(cents: u32) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
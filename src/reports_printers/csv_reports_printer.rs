@@ -0,0 +1,192 @@
+use std::{
+    io::{self, Write},
+    sync::Mutex,
+};
+
+use super::{beverage_name, format_euros, ingredient_name, unix_seconds};
+use crate::machine_system::{
+    beverages::{beverage::HotBeverageOption, cup_size::CupSize},
+    reports_printer::{PurchasesReport, ReportsPrinter, RestockReport},
+};
+use crate::shared::MaybeSend;
+
+pub struct CsvReportsPrinter<W: Write> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write> CsvReportsPrinter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap()
+    }
+
+    pub fn write_purchases_report(&self, purchases_report: &PurchasesReport) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer, "period_started_at,period_ended_at")?;
+        writeln!(
+            writer,
+            "{},{}",
+            unix_seconds(purchases_report.period_started_at),
+            unix_seconds(purchases_report.period_ended_at)
+        )?;
+        writeln!(writer)?;
+        writeln!(writer, "beverage,quantity,revenue,underpaid,shortages")?;
+        for beverage in purchases_report.sorted_beverages() {
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                csv_field(&beverage_name(beverage)),
+                purchases_report.quantity_of(beverage),
+                format_euros(purchases_report.revenue_of(beverage)),
+                purchases_report.underpaid_attempts_of(beverage),
+                purchases_report.shortage_attempts_of(beverage)
+            )?;
+        }
+        writeln!(
+            writer,
+            "total,{},{},{},{}",
+            purchases_report.total_quantity(),
+            format_euros(purchases_report.total_money_earned),
+            purchases_report.underpaid_attempts.values().sum::<u32>(),
+            purchases_report.shortage_attempts.values().sum::<u32>()
+        )?;
+        writeln!(writer)?;
+        writeln!(writer, "breakdown,quantity")?;
+        for (label, quantity) in [
+            (
+                "standard",
+                purchases_report.quantity_with_option(&HotBeverageOption::Standard),
+            ),
+            (
+                "extra hot",
+                purchases_report.quantity_with_option(&HotBeverageOption::ExtraHot),
+            ),
+            ("small", purchases_report.quantity_of_size(&CupSize::Small)),
+            (
+                "medium",
+                purchases_report.quantity_of_size(&CupSize::Medium),
+            ),
+            ("large", purchases_report.quantity_of_size(&CupSize::Large)),
+            ("sugar doses used", purchases_report.sugar_doses_used),
+            (
+                "stirring sticks used",
+                purchases_report.stirring_sticks_used,
+            ),
+        ] {
+            writeln!(writer, "{},{quantity}", csv_field(label))?;
+        }
+        writer.flush()
+    }
+
+    pub fn write_restock_report(&self, restock_report: &RestockReport) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer, "restocked_at,operator,ingredient,quantity")?;
+        for restock in &restock_report.restocks {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                unix_seconds(restock.restocked_at),
+                csv_field(&restock.operator),
+                csv_field(ingredient_name(&restock.ingredient)),
+                restock.quantity
+            )?;
+        }
+        writer.flush()
+    }
+}
+
+impl<W: Write + MaybeSend> ReportsPrinter for CsvReportsPrinter<W> {
+    fn print(&self, purchase_report: PurchasesReport) {
+        let _ = self.write_purchases_report(&purchase_report);
+    }
+
+    fn print_restock_report(&self, restock_report: RestockReport) {
+        let _ = self.write_restock_report(&restock_report);
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        String::from(value)
+    }
+}
+
+#[cfg(test)]
+mod csv_reports_printer_tests {
+    use std::{collections::HashMap, time::SystemTime};
+
+    use super::CsvReportsPrinter;
+    use crate::machine_system::{
+        beverages::{
+            beverage::{Beverage, HotBeverageOption},
+            ingredient::Ingredient,
+        },
+        reports_printer::{PurchasesReport, ReportsPrinter, RestockReport},
+        restock::RestockRecord,
+    };
+
+    #[test]
+    fn print_one_row_per_beverage_and_a_total_row() {
+        let printer = CsvReportsPrinter::new(vec![]);
+
+        printer.print(PurchasesReport::new(
+            &HashMap::from([
+                (Beverage::Tea(HotBeverageOption::Standard), 3),
+                (Beverage::Coffee(HotBeverageOption::Standard), 1),
+            ]),
+            &HashMap::from([
+                (Beverage::Tea(HotBeverageOption::Standard), 120),
+                (Beverage::Coffee(HotBeverageOption::Standard), 60),
+            ]),
+            180,
+            SystemTime::UNIX_EPOCH,
+            SystemTime::UNIX_EPOCH,
+        ));
+
+        assert_eq!(
+            String::from_utf8(printer.into_inner()).unwrap(),
+            "period_started_at,period_ended_at\n0,0\n\nbeverage,quantity,revenue,underpaid,shortages\ncoffee,1,0.60,0,0\ntea,3,1.20,0,0\ntotal,4,1.80,0,0\n\nbreakdown,quantity\nstandard,0\nextra hot,0\nsmall,0\nmedium,0\nlarge,0\nsugar doses used,0\nstirring sticks used,0\n"
+        )
+    }
+
+    #[test]
+    fn quote_beverage_names_containing_separators() {
+        let printer = CsvReportsPrinter::new(vec![]);
+        let beverage = Beverage::new("lemonade,\"fresh\"", None);
+
+        printer.print(PurchasesReport::new(
+            &HashMap::from([(beverage.clone(), 1)]),
+            &HashMap::from([(beverage, 70)]),
+            70,
+            SystemTime::UNIX_EPOCH,
+            SystemTime::UNIX_EPOCH,
+        ));
+
+        let printed_report = String::from_utf8(printer.into_inner()).unwrap();
+        assert!(printed_report.contains("\n\"lemonade,\"\"fresh\"\"\",1,0.70,0,0\n"))
+    }
+
+    #[test]
+    fn quote_operators_containing_separators() {
+        let printer = CsvReportsPrinter::new(vec![]);
+
+        printer.print_restock_report(RestockReport::new(&[RestockRecord {
+            operator: String::from("Doe, \"JD\""),
+            ingredient: Ingredient::WaterMl,
+            quantity: 5000,
+            restocked_at: SystemTime::UNIX_EPOCH,
+        }]));
+
+        assert_eq!(
+            String::from_utf8(printer.into_inner()).unwrap(),
+            "restocked_at,operator,ingredient,quantity\n0,\"Doe, \"\"JD\"\"\",water ml,5000\n"
+        )
+    }
+}
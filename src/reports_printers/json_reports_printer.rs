@@ -0,0 +1,162 @@
+use std::{
+    io::{self, Write},
+    sync::Mutex,
+};
+
+use super::{beverage_name, ingredient_name, unix_seconds};
+use crate::machine_system::{
+    beverages::{beverage::HotBeverageOption, cup_size::CupSize},
+    reports_printer::{PurchasesReport, ReportsPrinter, RestockReport},
+};
+use crate::shared::MaybeSend;
+
+pub struct JsonReportsPrinter<W: Write> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write> JsonReportsPrinter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap()
+    }
+
+    pub fn write_purchases_report(&self, purchases_report: &PurchasesReport) -> io::Result<()> {
+        let beverages: Vec<String> = purchases_report
+            .sorted_beverages()
+            .into_iter()
+            .map(|beverage| {
+                format!(
+                    "{{\"beverage\":{},\"quantity\":{},\"revenue_cents\":{},\"underpaid\":{},\"shortages\":{}}}",
+                    json_string(&beverage_name(beverage)),
+                    purchases_report.quantity_of(beverage),
+                    purchases_report.revenue_of(beverage),
+                    purchases_report.underpaid_attempts_of(beverage),
+                    purchases_report.shortage_attempts_of(beverage)
+                )
+            })
+            .collect();
+
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(
+            writer,
+            "{{\"period_started_at\":{},\"period_ended_at\":{},\"beverages\":[{}],\"total_quantity\":{},\"total_money_earned_cents\":{},\"standard_quantity\":{},\"extra_hot_quantity\":{},\"small_quantity\":{},\"medium_quantity\":{},\"large_quantity\":{},\"sugar_doses_used\":{},\"stirring_sticks_used\":{}}}",
+            unix_seconds(purchases_report.period_started_at),
+            unix_seconds(purchases_report.period_ended_at),
+            beverages.join(","),
+            purchases_report.total_quantity(),
+            purchases_report.total_money_earned,
+            purchases_report.quantity_with_option(&HotBeverageOption::Standard),
+            purchases_report.quantity_with_option(&HotBeverageOption::ExtraHot),
+            purchases_report.quantity_of_size(&CupSize::Small),
+            purchases_report.quantity_of_size(&CupSize::Medium),
+            purchases_report.quantity_of_size(&CupSize::Large),
+            purchases_report.sugar_doses_used,
+            purchases_report.stirring_sticks_used
+        )?;
+        writer.flush()
+    }
+
+    pub fn write_restock_report(&self, restock_report: &RestockReport) -> io::Result<()> {
+        let restocks: Vec<String> = restock_report
+            .restocks
+            .iter()
+            .map(|restock| {
+                format!(
+                    "{{\"operator\":{},\"ingredient\":{},\"quantity\":{},\"restocked_at\":{}}}",
+                    json_string(&restock.operator),
+                    json_string(ingredient_name(&restock.ingredient)),
+                    restock.quantity,
+                    unix_seconds(restock.restocked_at)
+                )
+            })
+            .collect();
+
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer, "{{\"restocks\":[{}]}}", restocks.join(","))?;
+        writer.flush()
+    }
+}
+
+impl<W: Write + MaybeSend> ReportsPrinter for JsonReportsPrinter<W> {
+    fn print(&self, purchase_report: PurchasesReport) {
+        let _ = self.write_purchases_report(&purchase_report);
+    }
+
+    fn print_restock_report(&self, restock_report: RestockReport) {
+        let _ = self.write_restock_report(&restock_report);
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for character in value.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            control if control.is_control() => {
+                escaped.push_str(&format!("\\u{:04x}", control as u32))
+            }
+            other => escaped.push(other),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod json_reports_printer_tests {
+    use std::{
+        collections::HashMap,
+        time::{Duration, SystemTime},
+    };
+
+    use super::JsonReportsPrinter;
+    use crate::machine_system::{
+        beverages::{beverage::Beverage, ingredient::Ingredient},
+        reports_printer::{PurchasesReport, ReportsPrinter, RestockReport},
+        restock::RestockRecord,
+    };
+
+    #[test]
+    fn print_purchases_report_as_a_json_object() {
+        let printer = JsonReportsPrinter::new(vec![]);
+
+        printer.print(PurchasesReport::new(
+            &HashMap::from([(Beverage::OrangeJuice, 2)]),
+            &HashMap::from([(Beverage::OrangeJuice, 120)]),
+            120,
+            SystemTime::UNIX_EPOCH,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(60),
+        ));
+
+        assert_eq!(
+            String::from_utf8(printer.into_inner()).unwrap(),
+            "{\"period_started_at\":0,\"period_ended_at\":60,\"beverages\":[{\"beverage\":\"orange juice\",\"quantity\":2,\"revenue_cents\":120,\"underpaid\":0,\"shortages\":0}],\"total_quantity\":2,\"total_money_earned_cents\":120,\"standard_quantity\":0,\"extra_hot_quantity\":0,\"small_quantity\":0,\"medium_quantity\":0,\"large_quantity\":0,\"sugar_doses_used\":0,\"stirring_sticks_used\":0}\n"
+        )
+    }
+
+    #[test]
+    fn escape_operator_names() {
+        let printer = JsonReportsPrinter::new(vec![]);
+
+        printer.print_restock_report(RestockReport::new(&[RestockRecord {
+            operator: String::from("\"bob\"\n"),
+            ingredient: Ingredient::SugarDoses,
+            quantity: 100,
+            restocked_at: SystemTime::UNIX_EPOCH,
+        }]));
+
+        assert_eq!(
+            String::from_utf8(printer.into_inner()).unwrap(),
+            "{\"restocks\":[{\"operator\":\"\\\"bob\\\"\\n\",\"ingredient\":\"sugar doses\",\"quantity\":100,\"restocked_at\":0}]}\n"
+        )
+    }
+}
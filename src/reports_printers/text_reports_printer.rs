@@ -0,0 +1,201 @@
+use std::{
+    io::{self, Write},
+    sync::Mutex,
+};
+
+use super::{beverage_name, format_euros, ingredient_name, unix_seconds};
+use crate::machine_system::{
+    beverages::{beverage::HotBeverageOption, cup_size::CupSize},
+    reports_printer::{PurchasesReport, ReportsPrinter, RestockReport},
+};
+use crate::shared::MaybeSend;
+
+const TOTAL_LABEL: &str = "Total";
+
+pub struct TextReportsPrinter<W: Write> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write> TextReportsPrinter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap()
+    }
+
+    pub fn write_purchases_report(&self, purchases_report: &PurchasesReport) -> io::Result<()> {
+        let beverages = purchases_report.sorted_beverages();
+        let name_width = beverages
+            .iter()
+            .map(|beverage| beverage_name(beverage).len())
+            .chain([TOTAL_LABEL.len(), "Beverage".len()])
+            .max()
+            .unwrap_or_default();
+
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(
+            writer,
+            "Purchases report from {} to {}",
+            unix_seconds(purchases_report.period_started_at),
+            unix_seconds(purchases_report.period_ended_at)
+        )?;
+        writeln!(
+            writer,
+            "{:<name_width$}  {:>8}  {:>10}  {:>9}  {:>9}",
+            "Beverage", "Quantity", "Revenue", "Underpaid", "Shortages"
+        )?;
+        for beverage in beverages {
+            writeln!(
+                writer,
+                "{:<name_width$}  {:>8}  {:>10}  {:>9}  {:>9}",
+                beverage_name(beverage),
+                purchases_report.quantity_of(beverage),
+                format!("{} €", format_euros(purchases_report.revenue_of(beverage))),
+                purchases_report.underpaid_attempts_of(beverage),
+                purchases_report.shortage_attempts_of(beverage)
+            )?;
+        }
+        writeln!(
+            writer,
+            "{:<name_width$}  {:>8}  {:>10}  {:>9}  {:>9}",
+            TOTAL_LABEL,
+            purchases_report.total_quantity(),
+            format!("{} €", format_euros(purchases_report.total_money_earned)),
+            purchases_report.underpaid_attempts.values().sum::<u32>(),
+            purchases_report.shortage_attempts.values().sum::<u32>()
+        )?;
+        writeln!(
+            writer,
+            "Standard: {}, extra hot: {}",
+            purchases_report.quantity_with_option(&HotBeverageOption::Standard),
+            purchases_report.quantity_with_option(&HotBeverageOption::ExtraHot)
+        )?;
+        writeln!(
+            writer,
+            "Small: {}, medium: {}, large: {}",
+            purchases_report.quantity_of_size(&CupSize::Small),
+            purchases_report.quantity_of_size(&CupSize::Medium),
+            purchases_report.quantity_of_size(&CupSize::Large)
+        )?;
+        writeln!(
+            writer,
+            "Sugar doses used: {}, stirring sticks used: {}",
+            purchases_report.sugar_doses_used, purchases_report.stirring_sticks_used
+        )?;
+        writer.flush()
+    }
+
+    pub fn write_restock_report(&self, restock_report: &RestockReport) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer, "Restock report")?;
+        for restock in &restock_report.restocks {
+            writeln!(
+                writer,
+                "{} {} restocked {} {}",
+                unix_seconds(restock.restocked_at),
+                restock.operator,
+                restock.quantity,
+                ingredient_name(&restock.ingredient)
+            )?;
+        }
+        writer.flush()
+    }
+}
+
+impl<W: Write + MaybeSend> ReportsPrinter for TextReportsPrinter<W> {
+    fn print(&self, purchase_report: PurchasesReport) {
+        let _ = self.write_purchases_report(&purchase_report);
+    }
+
+    fn print_restock_report(&self, restock_report: RestockReport) {
+        let _ = self.write_restock_report(&restock_report);
+    }
+}
+
+#[cfg(test)]
+mod text_reports_printer_tests {
+    use std::{
+        collections::HashMap,
+        time::{Duration, SystemTime},
+    };
+
+    use super::TextReportsPrinter;
+    use crate::machine_system::{
+        beverages::{
+            beverage::{Beverage, HotBeverageOption},
+            cup_size::CupSize,
+            ingredient::Ingredient,
+        },
+        reports_printer::{PurchasesReport, ReportsPrinter, RestockReport},
+        restock::RestockRecord,
+    };
+
+    #[test]
+    fn print_an_aligned_purchases_table_sorted_by_beverage() {
+        let printer = TextReportsPrinter::new(vec![]);
+
+        let mut purchases_report = PurchasesReport::new(
+            &HashMap::from([
+                (Beverage::OrangeJuice, 1),
+                (Beverage::Coffee(HotBeverageOption::ExtraHot), 2),
+                (Beverage::Coffee(HotBeverageOption::Standard), 10),
+            ]),
+            &HashMap::from([
+                (Beverage::OrangeJuice, 60),
+                (Beverage::Coffee(HotBeverageOption::ExtraHot), 120),
+                (Beverage::Coffee(HotBeverageOption::Standard), 600),
+            ]),
+            780,
+            SystemTime::UNIX_EPOCH,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(3600),
+        );
+        purchases_report.hot_options_quantities = HashMap::from([
+            (HotBeverageOption::Standard, 10),
+            (HotBeverageOption::ExtraHot, 2),
+        ]);
+        purchases_report.cup_sizes_quantities =
+            HashMap::from([(CupSize::Medium, 9), (CupSize::Large, 3)]);
+        purchases_report.sugar_doses_used = 5;
+        purchases_report.stirring_sticks_used = 3;
+        purchases_report.underpaid_attempts =
+            HashMap::from([(Beverage::Tea(HotBeverageOption::Standard), 1)]);
+        purchases_report.shortage_attempts = HashMap::from([(Beverage::OrangeJuice, 2)]);
+
+        printer.print(purchases_report);
+
+        assert_eq!(
+            String::from_utf8(printer.into_inner()).unwrap(),
+            "Purchases report from 0 to 3600\n\
+             Beverage          Quantity     Revenue  Underpaid  Shortages\n\
+             coffee                  10      6.00 €          0          0\n\
+             extra hot coffee         2      1.20 €          0          0\n\
+             tea                      0      0.00 €          1          0\n\
+             orange juice             1      0.60 €          0          2\n\
+             Total                   13      7.80 €          1          2\n\
+             Standard: 10, extra hot: 2\n\
+             Small: 0, medium: 9, large: 3\n\
+             Sugar doses used: 5, stirring sticks used: 3\n"
+        )
+    }
+
+    #[test]
+    fn print_one_line_per_restock() {
+        let printer = TextReportsPrinter::new(vec![]);
+
+        printer.print_restock_report(RestockReport::new(&[RestockRecord {
+            operator: String::from("alice"),
+            ingredient: Ingredient::TeaBags,
+            quantity: 50,
+            restocked_at: SystemTime::UNIX_EPOCH + Duration::from_secs(60),
+        }]));
+
+        assert_eq!(
+            String::from_utf8(printer.into_inner()).unwrap(),
+            "Restock report\n60 alice restocked 50 tea bags\n"
+        )
+    }
+}
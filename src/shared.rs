@@ -0,0 +1,35 @@
+//! Pointer and marker types used to hand collaborators to the machine.
+//!
+//! By default collaborators are shared through `Rc` and need not be thread
+//! safe. With the `sync` feature enabled they are shared through `Arc` and
+//! must be `Send + Sync`, so that a machine can be used from several threads.
+
+#[cfg(not(feature = "sync"))]
+pub type Shared<T> = std::rc::Rc<T>;
+
+#[cfg(feature = "sync")]
+pub type Shared<T> = std::sync::Arc<T>;
+
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSend {}
+
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSend for T {}
+
+#[cfg(feature = "sync")]
+pub trait MaybeSend: Send {}
+
+#[cfg(feature = "sync")]
+impl<T: ?Sized + Send> MaybeSend for T {}
+
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSendSync {}
+
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSendSync for T {}
+
+#[cfg(feature = "sync")]
+pub trait MaybeSendSync: Send + Sync {}
+
+#[cfg(feature = "sync")]
+impl<T: ?Sized + Send + Sync> MaybeSendSync for T {}
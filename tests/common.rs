@@ -1,3 +1,5 @@
+pub(crate) mod block_on;
+pub(crate) mod brewing_drink_maker_fake;
 pub(crate) mod drink_maker_test_double;
 pub(crate) mod dummy_notifier;
 pub(crate) mod dummy_reports_printer;
@@ -0,0 +1,78 @@
+use coffee_machine_kata_rust::{
+    drink_maker::AsyncDrinkMaker, machine_system::beverages::async_beverage_server::BoxFuture,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+pub(crate) struct BrewingDrinkMakerFake {
+    brewing_time: Duration,
+    received_commands: Mutex<Vec<String>>,
+}
+
+impl BrewingDrinkMakerFake {
+    pub(crate) fn new(brewing_time: Duration) -> Self {
+        Self {
+            brewing_time,
+            received_commands: Mutex::new(vec![]),
+        }
+    }
+
+    pub(crate) fn spied_received_commands(&self) -> Vec<String> {
+        self.received_commands.lock().unwrap().clone()
+    }
+}
+
+impl AsyncDrinkMaker for BrewingDrinkMakerFake {
+    fn execute(&self, command: String) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            Brewing::start(self.brewing_time).await;
+            self.received_commands.lock().unwrap().push(command);
+        })
+    }
+}
+
+#[derive(Default)]
+struct BrewingState {
+    done: bool,
+    waker: Option<Waker>,
+}
+
+struct Brewing {
+    state: Arc<Mutex<BrewingState>>,
+}
+
+impl Brewing {
+    fn start(brewing_time: Duration) -> Self {
+        let state = Arc::new(Mutex::new(BrewingState::default()));
+        let brewer_state = Arc::clone(&state);
+        thread::spawn(move || {
+            thread::sleep(brewing_time);
+            let mut state = brewer_state.lock().unwrap();
+            state.done = true;
+            if let Some(waker) = state.waker.take() {
+                waker.wake()
+            }
+        });
+        Self { state }
+    }
+}
+
+impl Future for Brewing {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.done {
+            Poll::Ready(())
+        } else {
+            state.waker = Some(context.waker().clone());
+            Poll::Pending
+        }
+    }
+}
@@ -1,23 +1,23 @@
 use coffee_machine_kata_rust::drink_maker::DrinkMaker;
-use std::cell::RefCell;
+use std::sync::Mutex;
 pub(crate) struct DrinkMakerTestDouble {
-    received_commands: RefCell<Vec<String>>,
+    received_commands: Mutex<Vec<String>>,
 }
 
 impl DrinkMakerTestDouble {
     pub(crate) fn new() -> Self {
         Self {
-            received_commands: RefCell::new(vec![]),
+            received_commands: Mutex::new(vec![]),
         }
     }
 
     pub(crate) fn spied_received_commands(&self) -> Vec<String> {
-        self.received_commands.clone().take()
+        self.received_commands.lock().unwrap().clone()
     }
 }
 
 impl DrinkMaker for DrinkMakerTestDouble {
     fn execute(&self, command: String) {
-        self.received_commands.borrow_mut().push(command);
+        self.received_commands.lock().unwrap().push(command);
     }
 }
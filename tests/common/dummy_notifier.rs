@@ -1,6 +1,22 @@
-use coffee_machine_kata_rust::machine_system::{beverages::beverage::Beverage, notifier::Notifier};
+use std::{io, time::SystemTime};
+
+use coffee_machine_kata_rust::machine_system::{
+    beverages::{beverage::Beverage, beverage_server::ServingFailure, ingredient::Ingredient},
+    machine_event::MachineEvent,
+    notifier::Notifier,
+};
 pub(crate) struct DummyNotifier {}
 
 impl Notifier for DummyNotifier {
-    fn notify_missing_beverage(&self, _drink: &Beverage) {}
+    fn notify_missing_beverage(&self, _drink: &Beverage, _detected_at: SystemTime) {}
+
+    fn notify_missing_ingredient(&self, _ingredient: &Ingredient, _detected_at: SystemTime) {}
+
+    fn notify_serving_failure(&self, _beverage: &Beverage, _serving_failure: &ServingFailure) {}
+
+    fn notify_low_stock(&self, _beverage: &Beverage, _remaining_servings: u32) {}
+
+    fn notify_low_ingredient_stock(&self, _ingredient: &Ingredient, _remaining_quantity: u32) {}
+
+    fn notify_event_store_failure(&self, _event: &MachineEvent, _error: &io::Error) {}
 }
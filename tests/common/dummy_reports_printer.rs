@@ -1,5 +1,9 @@
-use coffee_machine_kata_rust::machine_system::reports_printer::{PurchasesReport, ReportsPrinter};
+use coffee_machine_kata_rust::machine_system::reports_printer::{
+    PurchasesReport, ReportsPrinter, RestockReport,
+};
 pub(crate) struct DummyReportsPrinter {}
 impl ReportsPrinter for DummyReportsPrinter {
     fn print(&self, _purchase_report: PurchasesReport) {}
+
+    fn print_restock_report(&self, _restock_report: RestockReport) {}
 }
@@ -1,15 +1,22 @@
 mod common;
 
-use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use crate::common::{
+    block_on::block_on, brewing_drink_maker_fake::BrewingDrinkMakerFake,
     drink_maker_test_double::DrinkMakerTestDouble, dummy_notifier::DummyNotifier,
     dummy_reports_printer::DummyReportsPrinter,
 };
 use crate::test_doubles::*;
-use coffee_machine_kata_rust::prelude::DrinkMakerDisplay;
+use coffee_machine_kata_rust::prelude::{
+    BeverageCatalog, BeverageDefinition, Coin, DispenseError, DispenseOutcome, DrinkMakerDisplay,
+    Ingredient, IngredientInventory, Recipe, ServingFailure, Shared, Temperature,
+};
 use coffee_machine_kata_rust::{
-    drink_maker::drink_maker_beverage_server::DrinkMakerBeverageServer,
+    drink_maker::{
+        async_drink_maker_beverage_server::AsyncDrinkMakerBeverageServer,
+        drink_maker_beverage_server::DrinkMakerBeverageServer, SyncDrinkMakerAdapter,
+    },
     machine_system::{
         beverages::{
             beverage::Beverage, beverage::HotBeverageOption, beverage_request::BeverageRequest,
@@ -21,8 +28,11 @@ use coffee_machine_kata_rust::{
 use test_case::test_case;
 
 mod test_doubles {
-    use coffee_machine_kata_rust::prelude::{Beverage, BeverageQuantityChecker, Notifier};
-    use std::cell::RefCell;
+    use coffee_machine_kata_rust::prelude::{
+        AddOn, Beverage, BeverageQuantityChecker, CupSize, DrinkMaker, DrinkMakerError, Ingredient,
+        MachineEvent, Notifier, ServingFailure, SugarAmount,
+    };
+    use std::{io, sync::Mutex, time::SystemTime};
 
     pub(crate) const ENOUGH_MONEY: u32 = 100;
 
@@ -37,57 +47,104 @@ mod test_doubles {
     }
 
     impl BeverageQuantityChecker for BeverageQuantityCheckerFake {
-        fn is_empty(&self, _beverage: &Beverage) -> bool {
+        fn is_empty(
+            &self,
+            _beverage: &Beverage,
+            _sugar_amount: &SugarAmount,
+            _cup_size: &CupSize,
+            _add_ons: &[AddOn],
+        ) -> bool {
             self.always_empty
         }
     }
 
+    pub(crate) struct JammedDrinkMakerTestDouble {
+        received_commands: Mutex<Vec<String>>,
+    }
+
+    impl JammedDrinkMakerTestDouble {
+        pub(crate) fn new() -> Self {
+            Self {
+                received_commands: Mutex::new(vec![]),
+            }
+        }
+
+        pub(crate) fn spied_received_commands(&self) -> Vec<String> {
+            self.received_commands.lock().unwrap().clone()
+        }
+    }
+
+    impl DrinkMaker for JammedDrinkMakerTestDouble {
+        fn execute(&self, command: String) {
+            self.received_commands.lock().unwrap().push(command);
+        }
+
+        fn try_execute(&self, command: String) -> Result<(), DrinkMakerError> {
+            self.execute(command);
+            Err(DrinkMakerError::Nack(3))
+        }
+    }
+
     pub(crate) struct NotifierTestDouble {
-        missing_beverages_notifications: RefCell<Vec<Beverage>>,
+        missing_beverages_notifications: Mutex<Vec<Beverage>>,
     }
 
     impl NotifierTestDouble {
         pub(crate) fn new() -> Self {
             Self {
-                missing_beverages_notifications: RefCell::new(Vec::new()),
+                missing_beverages_notifications: Mutex::new(Vec::new()),
             }
         }
 
         pub(crate) fn spied_missing_beverages_messages(&self) -> Vec<Beverage> {
-            self.missing_beverages_notifications.borrow().clone()
+            self.missing_beverages_notifications.lock().unwrap().clone()
         }
     }
 
     impl Notifier for NotifierTestDouble {
-        fn notify_missing_beverage(&self, drink: &Beverage) {
+        fn notify_missing_beverage(&self, drink: &Beverage, _detected_at: SystemTime) {
             self.missing_beverages_notifications
-                .borrow_mut()
+                .lock()
+                .unwrap()
                 .push(drink.clone())
         }
+
+        fn notify_missing_ingredient(&self, _ingredient: &Ingredient, _detected_at: SystemTime) {}
+
+        fn notify_serving_failure(&self, _beverage: &Beverage, _serving_failure: &ServingFailure) {}
+
+        fn notify_low_stock(&self, _beverage: &Beverage, _remaining_servings: u32) {}
+
+        fn notify_low_ingredient_stock(&self, _ingredient: &Ingredient, _remaining_quantity: u32) {}
+
+        fn notify_event_store_failure(&self, _event: &MachineEvent, _error: &io::Error) {}
     }
 }
 
-#[test_case(Beverage::Coffee(HotBeverageOption::Standard), SugarAmount::One, "C:1:0" ; "coffee")]
-#[test_case(Beverage::Coffee(HotBeverageOption::ExtraHot), SugarAmount::Zero, "Ch::" ; "extra hot coffee")]
-#[test_case(Beverage::Tea(HotBeverageOption::ExtraHot), SugarAmount::Two, "Th:2:0" ; "extra hot tea")]
+#[test_case(Beverage::Coffee(HotBeverageOption::Standard), SugarAmount::One, "C:1:0", "M:Change returned: 0.4€" ; "coffee")]
+#[test_case(Beverage::Coffee(HotBeverageOption::ExtraHot), SugarAmount::Zero, "Ch::", "M:Change returned: 0.4€" ; "extra hot coffee")]
+#[test_case(Beverage::Tea(HotBeverageOption::ExtraHot), SugarAmount::Two, "Th:2:0", "M:Change returned: 0.6€" ; "extra hot tea")]
 fn machine_dispenses_beverage(
     beverage: Beverage,
     sugar_amount: SugarAmount,
     expected_drink_maker_cmd: &str,
+    expected_change_message: &str,
 ) {
-    let drink_maker_test_double = Rc::new(DrinkMakerTestDouble::new());
-    let beverage_server = Rc::new(DrinkMakerBeverageServer::new(Rc::clone(
+    let drink_maker_test_double = Shared::new(DrinkMakerTestDouble::new());
+    let beverage_server = Shared::new(DrinkMakerBeverageServer::new(Shared::clone(
         &drink_maker_test_double,
     )));
     let beverage_quantity_checker_fake_always_full =
-        Rc::new(BeverageQuantityCheckerFake::new(false));
-    let drink_maker_display = Rc::new(DrinkMakerDisplay::new(Rc::clone(&drink_maker_test_double)));
+        Shared::new(BeverageQuantityCheckerFake::new(false));
+    let drink_maker_display = Shared::new(DrinkMakerDisplay::new(Shared::clone(
+        &drink_maker_test_double,
+    )));
     let mut machine = MachineBuilder::default()
         .set(beverage_server)
         .set(beverage_quantity_checker_fake_always_full)
         .set(drink_maker_display)
-        .set(Rc::new(DummyReportsPrinter {}))
-        .set(Rc::new(DummyNotifier {}))
+        .set(Shared::new(DummyReportsPrinter {}))
+        .set(Shared::new(DummyNotifier {}))
         .build();
 
     let beverage_request = BeverageRequest::new(&beverage, &sugar_amount, ENOUGH_MONEY);
@@ -96,7 +153,10 @@ fn machine_dispenses_beverage(
     let drink_maker_cmds = drink_maker_test_double.spied_received_commands();
     assert_eq!(
         drink_maker_cmds,
-        vec![(String::from(expected_drink_maker_cmd))]
+        vec![
+            String::from(expected_drink_maker_cmd),
+            String::from(expected_change_message)
+        ]
     )
 }
 
@@ -109,19 +169,21 @@ fn machine_requires_money_to_dispense_beverage(
     money_amount: u32,
     expected_drink_maker_cmd: &str,
 ) {
-    let drink_maker_test_double = Rc::new(DrinkMakerTestDouble::new());
-    let beverage_server = Rc::new(DrinkMakerBeverageServer::new(Rc::clone(
+    let drink_maker_test_double = Shared::new(DrinkMakerTestDouble::new());
+    let beverage_server = Shared::new(DrinkMakerBeverageServer::new(Shared::clone(
         &drink_maker_test_double,
     )));
     let beverage_quantity_checker_fake_always_full =
-        Rc::new(BeverageQuantityCheckerFake::new(false));
-    let drink_maker_display = Rc::new(DrinkMakerDisplay::new(Rc::clone(&drink_maker_test_double)));
+        Shared::new(BeverageQuantityCheckerFake::new(false));
+    let drink_maker_display = Shared::new(DrinkMakerDisplay::new(Shared::clone(
+        &drink_maker_test_double,
+    )));
     let mut machine = MachineBuilder::default()
         .set(beverage_server)
         .set(beverage_quantity_checker_fake_always_full)
         .set(drink_maker_display)
-        .set(Rc::new(DummyReportsPrinter {}))
-        .set(Rc::new(DummyNotifier {}))
+        .set(Shared::new(DummyReportsPrinter {}))
+        .set(Shared::new(DummyNotifier {}))
         .build();
 
     let beverage_request = BeverageRequest::new(&beverage, &SugarAmount::Zero, money_amount);
@@ -136,18 +198,20 @@ fn machine_requires_money_to_dispense_beverage(
 
 #[test_case(Beverage::OrangeJuice, "M:Sorry, orange juice is empty." ; "orane juice empty")]
 fn machine_handles_beverage_shortage(beverage: Beverage, expected_missing_beverage_message: &str) {
-    let drink_maker_spy = Rc::new(DrinkMakerTestDouble::new());
-    let beverage_server = Rc::new(DrinkMakerBeverageServer::new(Rc::clone(&drink_maker_spy)));
+    let drink_maker_spy = Shared::new(DrinkMakerTestDouble::new());
+    let beverage_server = Shared::new(DrinkMakerBeverageServer::new(Shared::clone(
+        &drink_maker_spy,
+    )));
     let beverage_quantity_checker_fake_always_full =
-        Rc::new(BeverageQuantityCheckerFake::new(true));
-    let drink_maker_display = Rc::new(DrinkMakerDisplay::new(Rc::clone(&drink_maker_spy)));
-    let notifier_test_double = Rc::new(NotifierTestDouble::new());
+        Shared::new(BeverageQuantityCheckerFake::new(true));
+    let drink_maker_display = Shared::new(DrinkMakerDisplay::new(Shared::clone(&drink_maker_spy)));
+    let notifier_test_double = Shared::new(NotifierTestDouble::new());
     let mut machine = MachineBuilder::default()
         .set(beverage_server)
         .set(beverage_quantity_checker_fake_always_full)
         .set(drink_maker_display)
-        .set(Rc::new(DummyReportsPrinter {}))
-        .set(Rc::clone(&notifier_test_double))
+        .set(Shared::new(DummyReportsPrinter {}))
+        .set(Shared::clone(&notifier_test_double))
         .build();
 
     let beverage_request = BeverageRequest::new(&beverage, &SugarAmount::Zero, ENOUGH_MONEY);
@@ -161,3 +225,194 @@ fn machine_handles_beverage_shortage(beverage: Beverage, expected_missing_bevera
     );
     assert_eq!(missing_beverages_messages, vec![Beverage::OrangeJuice]);
 }
+
+fn catalog_with_espresso() -> BeverageCatalog {
+    BeverageCatalog::default()
+        .with_definition(BeverageDefinition {
+            id: String::from("espresso"),
+            display_name: String::from("house espresso"),
+            drink_maker_code: String::from("E"),
+            price: 80,
+            temperature: Temperature::Hot,
+            allowed_options: vec![HotBeverageOption::Standard],
+            allowed_add_ons: vec![],
+            max_sugar_doses: 2,
+            recipe: Recipe::default().with(Ingredient::CoffeeGrams, 8),
+        })
+        .unwrap()
+}
+
+#[test]
+fn machine_sends_the_drink_maker_code_of_its_catalog() {
+    let drink_maker_spy = Shared::new(DrinkMakerTestDouble::new());
+    let mut machine = MachineBuilder::default()
+        .set(Shared::new(DrinkMakerBeverageServer::new(Shared::clone(
+            &drink_maker_spy,
+        ))))
+        .set(Shared::new(BeverageQuantityCheckerFake::new(false)))
+        .set(Shared::new(DrinkMakerDisplay::new(Shared::clone(
+            &drink_maker_spy,
+        ))))
+        .set(Shared::new(DummyReportsPrinter {}))
+        .set(Shared::new(DummyNotifier {}))
+        .with_beverage_catalog(catalog_with_espresso())
+        .build();
+
+    let outcome = machine.dispense(BeverageRequest::new(
+        &Beverage::new("espresso", Some(HotBeverageOption::Standard)),
+        &SugarAmount::Zero,
+        ENOUGH_MONEY,
+    ));
+
+    assert_eq!(outcome, DispenseOutcome::Served { change: 20 });
+    assert_eq!(
+        drink_maker_spy.spied_received_commands(),
+        vec![String::from("E::"), String::from("M:Change returned: 0.2€")]
+    )
+}
+
+#[test]
+fn machine_checks_stock_and_names_beverages_with_its_catalog() {
+    let drink_maker_spy = Shared::new(DrinkMakerTestDouble::new());
+    let mut machine = MachineBuilder::default()
+        .set(Shared::new(DrinkMakerBeverageServer::new(Shared::clone(
+            &drink_maker_spy,
+        ))))
+        .set(Shared::new(IngredientInventory::default()))
+        .set(Shared::new(DrinkMakerDisplay::new(Shared::clone(
+            &drink_maker_spy,
+        ))))
+        .set(Shared::new(DummyReportsPrinter {}))
+        .set(Shared::new(DummyNotifier {}))
+        .with_beverage_catalog(catalog_with_espresso())
+        .build();
+
+    let outcome = machine.dispense(BeverageRequest::new(
+        &Beverage::new("espresso", Some(HotBeverageOption::Standard)),
+        &SugarAmount::Zero,
+        ENOUGH_MONEY,
+    ));
+
+    assert!(matches!(
+        outcome,
+        DispenseOutcome::Failed(DispenseError::Shortage { .. })
+    ));
+    assert_eq!(
+        drink_maker_spy.spied_received_commands(),
+        vec![String::from("M:Sorry, house espresso is empty.")]
+    )
+}
+
+#[test]
+fn machine_handles_jammed_drink_maker() {
+    let drink_maker_spy = Shared::new(JammedDrinkMakerTestDouble::new());
+    let beverage_server = Shared::new(DrinkMakerBeverageServer::new(Shared::clone(
+        &drink_maker_spy,
+    )));
+    let beverage_quantity_checker_fake_always_full =
+        Shared::new(BeverageQuantityCheckerFake::new(false));
+    let drink_maker_display = Shared::new(DrinkMakerDisplay::new(Shared::clone(&drink_maker_spy)));
+    let mut machine = MachineBuilder::default()
+        .set(beverage_server)
+        .set(beverage_quantity_checker_fake_always_full)
+        .set(drink_maker_display)
+        .set(Shared::new(DummyReportsPrinter {}))
+        .set(Shared::new(DummyNotifier {}))
+        .build();
+
+    let beverage_request = BeverageRequest::new(
+        &Beverage::Tea(HotBeverageOption::Standard),
+        &SugarAmount::Zero,
+        ENOUGH_MONEY,
+    );
+    let outcome = machine.dispense(beverage_request);
+
+    assert_eq!(
+        drink_maker_spy.spied_received_commands(),
+        vec![
+            String::from("T::"),
+            String::from("M:Sorry, unable to serve tea. Money refunded.")
+        ]
+    );
+    assert_eq!(
+        outcome,
+        DispenseOutcome::Failed(DispenseError::ServingFailed {
+            failure: ServingFailure::Rejected(3),
+            refunded: ENOUGH_MONEY,
+            refunded_coins: vec![Coin::OneEuro]
+        })
+    );
+}
+
+#[test]
+fn machine_awaits_brewing_when_dispensing_asynchronously() {
+    let brewing_time = Duration::from_millis(50);
+    let brewing_drink_maker_fake = Shared::new(BrewingDrinkMakerFake::new(brewing_time));
+    let drink_maker_spy = Shared::new(DrinkMakerTestDouble::new());
+    let mut machine = MachineBuilder::default()
+        .set(Shared::new(DrinkMakerBeverageServer::new(Shared::clone(
+            &drink_maker_spy,
+        ))))
+        .set(Shared::new(BeverageQuantityCheckerFake::new(false)))
+        .set(Shared::new(DrinkMakerDisplay::new(Shared::clone(
+            &drink_maker_spy,
+        ))))
+        .set(Shared::new(DummyReportsPrinter {}))
+        .set(Shared::new(DummyNotifier {}))
+        .with_async_beverage_server(Shared::new(AsyncDrinkMakerBeverageServer::new(
+            Shared::clone(&brewing_drink_maker_fake),
+        )))
+        .build();
+
+    let started_at = Instant::now();
+    let outcome = block_on(machine.dispense_async(BeverageRequest::new(
+        &Beverage::Coffee(HotBeverageOption::Standard),
+        &SugarAmount::One,
+        ENOUGH_MONEY,
+    )));
+
+    assert!(started_at.elapsed() >= brewing_time);
+    assert_eq!(outcome, DispenseOutcome::Served { change: 40 });
+    assert_eq!(
+        brewing_drink_maker_fake.spied_received_commands(),
+        vec![String::from("C:1:0")]
+    );
+    assert_eq!(
+        drink_maker_spy.spied_received_commands(),
+        vec![String::from("M:Change returned: 0.4€")]
+    )
+}
+
+#[test]
+fn machine_dispenses_asynchronously_through_a_sync_drink_maker() {
+    let drink_maker_spy = Shared::new(DrinkMakerTestDouble::new());
+    let mut machine = MachineBuilder::default()
+        .set(Shared::new(DrinkMakerBeverageServer::new(Shared::clone(
+            &drink_maker_spy,
+        ))))
+        .set(Shared::new(BeverageQuantityCheckerFake::new(false)))
+        .set(Shared::new(DrinkMakerDisplay::new(Shared::clone(
+            &drink_maker_spy,
+        ))))
+        .set(Shared::new(DummyReportsPrinter {}))
+        .set(Shared::new(DummyNotifier {}))
+        .with_async_beverage_server(Shared::new(AsyncDrinkMakerBeverageServer::new(
+            Shared::new(SyncDrinkMakerAdapter::new(Shared::clone(&drink_maker_spy))),
+        )))
+        .build();
+
+    let outcome = block_on(machine.dispense_async(BeverageRequest::new(
+        &Beverage::Tea(HotBeverageOption::ExtraHot),
+        &SugarAmount::Zero,
+        ENOUGH_MONEY,
+    )));
+
+    assert_eq!(outcome, DispenseOutcome::Served { change: 60 });
+    assert_eq!(
+        drink_maker_spy.spied_received_commands(),
+        vec![
+            String::from("Th::"),
+            String::from("M:Change returned: 0.6€")
+        ]
+    )
+}
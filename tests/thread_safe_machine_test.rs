@@ -0,0 +1,119 @@
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    thread,
+    time::SystemTime,
+};
+
+use coffee_machine_kata_rust::machine_system::sales_ledger::total_money_earned;
+use coffee_machine_kata_rust::prelude::{
+    AddOn, Beverage, BeverageQuantityChecker, BeverageRequest, CupSize, DrinkMaker,
+    DrinkMakerBeverageServer, DrinkMakerDisplay, HotBeverageOption, Ingredient, MachineBuilder,
+    MachineEvent, Notifier, PurchasesReport, ReportsPrinter, RestockReport, ServingFailure,
+    SugarAmount,
+};
+
+struct DrinkMakerSpy {
+    received_commands: Mutex<Vec<String>>,
+}
+
+impl DrinkMaker for DrinkMakerSpy {
+    fn execute(&self, command: String) {
+        self.received_commands.lock().unwrap().push(command);
+    }
+}
+
+struct AlwaysFullBeverageQuantityChecker {}
+
+impl BeverageQuantityChecker for AlwaysFullBeverageQuantityChecker {
+    fn is_empty(
+        &self,
+        _beverage: &Beverage,
+        _sugar_amount: &SugarAmount,
+        _cup_size: &CupSize,
+        _add_ons: &[AddOn],
+    ) -> bool {
+        false
+    }
+}
+
+struct DummyReportsPrinter {}
+
+impl ReportsPrinter for DummyReportsPrinter {
+    fn print(&self, _purchase_report: PurchasesReport) {}
+
+    fn print_restock_report(&self, _restock_report: RestockReport) {}
+}
+
+struct DummyNotifier {}
+
+impl Notifier for DummyNotifier {
+    fn notify_missing_beverage(&self, _drink: &Beverage, _detected_at: SystemTime) {}
+
+    fn notify_missing_ingredient(&self, _ingredient: &Ingredient, _detected_at: SystemTime) {}
+
+    fn notify_serving_failure(&self, _beverage: &Beverage, _serving_failure: &ServingFailure) {}
+
+    fn notify_low_stock(&self, _beverage: &Beverage, _remaining_servings: u32) {}
+
+    fn notify_low_ingredient_stock(&self, _ingredient: &Ingredient, _remaining_quantity: u32) {}
+
+    fn notify_event_store_failure(&self, _event: &MachineEvent, _error: &io::Error) {}
+}
+
+#[test]
+fn machine_shared_between_threads_serves_every_request() {
+    const THREADS: usize = 8;
+    const REQUESTS_PER_THREAD: usize = 25;
+    const COFFEE_PRICE: u32 = 60;
+    let drink_maker_spy = Arc::new(DrinkMakerSpy {
+        received_commands: Mutex::new(vec![]),
+    });
+    let beverage_server = Arc::new(DrinkMakerBeverageServer::new(Arc::clone(&drink_maker_spy)));
+    let beverage_quantity_checker_fake_always_full = Arc::new(AlwaysFullBeverageQuantityChecker {});
+    let drink_maker_display = Arc::new(DrinkMakerDisplay::new(Arc::clone(&drink_maker_spy)));
+    let machine = Arc::new(Mutex::new(
+        MachineBuilder::default()
+            .set(beverage_server)
+            .set(beverage_quantity_checker_fake_always_full)
+            .set(drink_maker_display)
+            .set(Arc::new(DummyReportsPrinter {}))
+            .set(Arc::new(DummyNotifier {}))
+            .build(),
+    ));
+
+    let customers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let machine = Arc::clone(&machine);
+            thread::spawn(move || {
+                for _ in 0..REQUESTS_PER_THREAD {
+                    let beverage_request = BeverageRequest::new(
+                        &Beverage::Coffee(HotBeverageOption::Standard),
+                        &SugarAmount::Zero,
+                        COFFEE_PRICE,
+                    );
+                    machine.lock().unwrap().dispense(beverage_request);
+                }
+            })
+        })
+        .collect();
+    for customer in customers {
+        customer.join().unwrap();
+    }
+
+    let machine = machine.lock().unwrap();
+    let served_requests = THREADS * REQUESTS_PER_THREAD;
+    assert_eq!(machine.sales_ledger().entries().len(), served_requests);
+    assert_eq!(
+        total_money_earned(machine.sales_ledger().entries()),
+        served_requests as u32 * COFFEE_PRICE
+    );
+    let received_commands = drink_maker_spy.received_commands.lock().unwrap();
+    assert_eq!(
+        received_commands
+            .iter()
+            .filter(|command| *command == "C::")
+            .count(),
+        served_requests
+    );
+}